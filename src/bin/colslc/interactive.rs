@@ -0,0 +1,330 @@
+use std::error::Error;
+use std::io::{self, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+
+use inslice::filter::Indexing;
+
+/// Actions that can be taken in the picker, decoupled from the keys that trigger them so that
+/// the picker can be driven without a terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Left,
+    Right,
+    Toggle,
+    All,
+    Clear,
+    Accept,
+    Cancel,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Accepted(Vec<String>),
+    Cancelled,
+}
+
+/// A Picker holds the state of the interactive column picker, which previews the first few
+/// lines of the input text split into numbered columns, and lets the user toggle which columns
+/// should be selected.
+pub struct Picker {
+    rows: Vec<Vec<String>>,
+    selected: Vec<bool>,
+    cursor: usize,
+    indexing: Indexing,
+}
+
+impl Picker {
+    pub fn new(rows: Vec<Vec<String>>) -> Self {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        Self {
+            rows,
+            selected: vec![false; columns],
+            cursor: 0,
+            indexing: Indexing::OneBased,
+        }
+    }
+
+    /// Sets the indexing that columns are numbered in, so that the filters printed select the
+    /// same columns when given to colslc with the same indexing.
+    pub fn indexing(mut self, indexing: Indexing) -> Self {
+        self.indexing = indexing;
+        self
+    }
+
+    /// Applies an action to the picker, returning the outcome once the picker has been either
+    /// accepted or cancelled.
+    pub fn handle(&mut self, action: Action) -> Option<Outcome> {
+        match action {
+            Action::Left => self.cursor = self.cursor.saturating_sub(1),
+            Action::Right => {
+                if self.cursor + 1 < self.selected.len() {
+                    self.cursor += 1;
+                }
+            }
+            Action::Toggle => {
+                if let Some(selected) = self.selected.get_mut(self.cursor) {
+                    *selected = !*selected;
+                }
+            }
+            Action::All => self.selected.iter_mut().for_each(|s| *s = true),
+            Action::Clear => self.selected.iter_mut().for_each(|s| *s = false),
+            Action::Accept => return Some(Outcome::Accepted(self.filters())),
+            Action::Cancel => return Some(Outcome::Cancelled),
+        }
+
+        None
+    }
+
+    /// Returns the filters equivalent to the selected columns, written in the picker's indexing,
+    /// with runs of consecutive columns combined into ranges.
+    pub fn filters(&self) -> Vec<String> {
+        let mut filters = Vec::new();
+        let mut start: Option<usize> = None;
+
+        for index in 0..=self.selected.len() {
+            let selected = self.selected.get(index).copied().unwrap_or(false);
+            match (selected, start) {
+                (true, None) => start = Some(index),
+                (false, Some(s)) => {
+                    filters.push(match (index - s, self.indexing) {
+                        (1, Indexing::OneBased) => format!("{}", 1 + s),
+                        (1, _) => format!("{}", s),
+                        (_, Indexing::OneBased) => format!("{}:{}", 1 + s, index),
+                        (_, Indexing::ZeroBased) => format!("{}:{}", s, index - 1),
+                        (_, Indexing::Python) => format!("{}:{}", s, index),
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        filters
+    }
+
+    /// Renders the picker as lines of text, with a row of column numbers and a row of markers
+    /// showing the cursor and the selected columns above the preview.
+    pub fn render(&self) -> Vec<String> {
+        let widths: Vec<usize> = (0..self.selected.len())
+            .map(|index| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(index))
+                    .map(|col| col.chars().count())
+                    .chain(Some(4))
+                    .max()
+                    .unwrap_or(4)
+            })
+            .collect();
+
+        let line = |cells: Vec<String>| -> String {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{:width$}", cell, width = width))
+                .collect();
+            padded.join("  ").trim_end().to_owned()
+        };
+
+        let mut lines = Vec::new();
+        let first = match self.indexing {
+            Indexing::OneBased => 1,
+            _ => 0,
+        };
+        lines.push(line(
+            (first..first + widths.len())
+                .map(|n| n.to_string())
+                .collect(),
+        ));
+        lines.push(line(
+            self.selected
+                .iter()
+                .enumerate()
+                .map(|(index, &selected)| {
+                    let cursor = if index == self.cursor { ">" } else { " " };
+                    let mark = if selected { "[x]" } else { "[ ]" };
+                    format!("{}{}", cursor, mark)
+                })
+                .collect(),
+        ));
+        for row in self.rows.iter() {
+            lines.push(line(row.clone()));
+        }
+
+        lines
+    }
+}
+
+fn action(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Left | KeyCode::Char('h') => Some(Action::Left),
+        KeyCode::Right | KeyCode::Char('l') => Some(Action::Right),
+        KeyCode::Char(' ') => Some(Action::Toggle),
+        KeyCode::Char('a') => Some(Action::All),
+        KeyCode::Char('n') => Some(Action::Clear),
+        KeyCode::Enter => Some(Action::Accept),
+        KeyCode::Esc | KeyCode::Char('q') => Some(Action::Cancel),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Cancel),
+        _ => None,
+    }
+}
+
+/// Runs the picker in the terminal, drawing to standard error so that the filters chosen can be
+/// written to standard output once the picker is accepted.
+pub fn run(picker: &mut Picker) -> Result<Outcome, Box<dyn Error>> {
+    let mut screen = io::stderr();
+
+    terminal::enable_raw_mode()?;
+    execute!(screen, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let outcome = event_loop(picker, &mut screen);
+
+    execute!(screen, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    outcome
+}
+
+fn event_loop<W: Write>(picker: &mut Picker, screen: &mut W) -> Result<Outcome, Box<dyn Error>> {
+    loop {
+        draw(picker, screen)?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if let Some(outcome) = action(key).and_then(|action| picker.handle(action)) {
+                return Ok(outcome);
+            }
+        }
+    }
+}
+
+fn draw<W: Write>(picker: &Picker, screen: &mut W) -> Result<(), Box<dyn Error>> {
+    queue!(
+        screen,
+        terminal::Clear(ClearType::All),
+        cursor::MoveTo(0, 0)
+    )?;
+
+    for (n, line) in picker.render().iter().enumerate() {
+        if n < 2 {
+            queue!(screen, SetAttribute(Attribute::Bold))?;
+        }
+        queue!(
+            screen,
+            Print(line),
+            SetAttribute(Attribute::Reset),
+            Print("\r\n")
+        )?;
+    }
+
+    let filters = picker.filters();
+    queue!(
+        screen,
+        Print("\r\n"),
+        Print(format!("-f {}\r\n", filters.join(" "))),
+        Print("\r\n"),
+        Print("←/→ move  space toggle  a all  n none  enter accept  q cancel\r\n")
+    )?;
+
+    screen.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_picker() -> Picker {
+        Picker::new(vec![
+            vec!["REPOSITORY".to_owned(), "TAG".to_owned(), "SIZE".to_owned()],
+            vec!["vault".to_owned(), "1.8.4".to_owned(), "186MB".to_owned()],
+        ])
+    }
+
+    #[test]
+    fn picker_accept_ok() {
+        let mut picker = new_picker();
+
+        let actions = [Action::Toggle, Action::Right, Action::Right, Action::Toggle];
+        for action in actions.iter() {
+            assert_eq!(picker.handle(*action), None);
+        }
+
+        assert_eq!(
+            picker.handle(Action::Accept),
+            Some(Outcome::Accepted(vec!["1".to_owned(), "3".to_owned()]))
+        );
+    }
+
+    #[test]
+    fn picker_ranges_ok() {
+        let mut picker = new_picker();
+        picker.handle(Action::All);
+        picker.handle(Action::Right);
+        picker.handle(Action::Right);
+        picker.handle(Action::Right);
+        picker.handle(Action::Toggle);
+
+        assert_eq!(picker.filters(), vec!["1:2".to_owned()]);
+    }
+
+    #[test]
+    fn picker_indexing_ok() {
+        let select = |indexing: Indexing| {
+            let mut picker = new_picker().indexing(indexing);
+            picker.handle(Action::All);
+            picker.handle(Action::Right);
+            picker.handle(Action::Toggle);
+            picker.handle(Action::Right);
+            picker.handle(Action::Right);
+            picker.filters()
+        };
+
+        assert_eq!(select(Indexing::OneBased), vec!["1", "3"]);
+        assert_eq!(select(Indexing::ZeroBased), vec!["0", "2"]);
+        assert_eq!(select(Indexing::Python), vec!["0", "2"]);
+
+        let ranges = |indexing: Indexing| {
+            let mut picker = new_picker().indexing(indexing);
+            picker.handle(Action::All);
+            picker.filters()
+        };
+
+        assert_eq!(ranges(Indexing::OneBased), vec!["1:3"]);
+        assert_eq!(ranges(Indexing::ZeroBased), vec!["0:2"]);
+        assert_eq!(ranges(Indexing::Python), vec!["0:3"]);
+    }
+
+    #[test]
+    fn picker_cancel_ok() {
+        let mut picker = new_picker();
+        picker.handle(Action::Toggle);
+
+        assert_eq!(picker.handle(Action::Cancel), Some(Outcome::Cancelled));
+    }
+
+    #[test]
+    fn picker_render_ok() {
+        let mut picker = new_picker();
+        picker.handle(Action::Right);
+        picker.handle(Action::Toggle);
+
+        assert_eq!(
+            picker.render(),
+            vec![
+                "1           2      3",
+                " [ ]        >[x]    [ ]",
+                "REPOSITORY  TAG    SIZE",
+                "vault       1.8.4  186MB",
+            ]
+        );
+    }
+}
@@ -1,11 +1,27 @@
+use std::env;
 use std::error::Error;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::process;
+use std::str::FromStr;
 
-use clap::Parser;
+use clap::{ArgEnum, IntoApp, Parser};
+use regex::Regex;
+use serde_json::Value;
 
-use inslice::filter::{Filter, FilterSet};
+use inslice::filter::{self, FilterSet, Indexing};
+use inslice::generate::Generate;
+use inslice::path::FieldPath;
+use inslice::preset::{self, Config};
+use inslice::record::{self, FinalNewline, LineEnding, ReadRecord, RecordWriter};
+
+use crate::output::{Column, OutputFormat};
+use crate::split::{Delimiter, Splitter};
+
+#[cfg(feature = "interactive")]
+mod interactive;
+mod output;
+mod split;
 
 /// A command-line utility for filtering input text by columns and writing them to standard output
 #[derive(Parser)]
@@ -34,17 +50,207 @@ struct Opts {
     ///     
     /// * [:n] - a range-based filter for selecting the first to last (inclusive) columns
     ///
+    /// * [m:n] - a range-based filter where m is greater than n, for selecting the n'th to m'th
+    ///   (inclusive) columns and writing them in reverse order in place of the range
+    ///
+    /// * [-n] - an index counted backwards from the last column of each line, such that -1 is the
+    ///   last column, which can be used as either bound of a range, such as 2:-2 for all but the
+    ///   first and last columns
+    ///
+    /// * [p%] - a percentage of the columns of each line, which can be used as either bound of a
+    ///   range, such as 50%: for the second half of the columns, or on its own for the column
+    ///   at that percentage
+    ///
     /// Example:
     ///
     /// `colslc - -f 1 4:6` will result in the 1st, 4th, 5th, and 6th columns of the input text
     /// provided from standard input being written to standard output, separated by whitespace.
-    #[clap(short, long)]
-    filters: Vec<Filter>,
+    ///
+    /// When reading JSON Lines input, filters are instead comma-separated lists of field paths,
+    /// such as `level,http.status`. Path segments that are valid filters select elements from
+    /// arrays by their position, such as `tags.1` or `tags.2:`.
+    #[clap(short, long, multiple_occurrences = true)]
+    filters: Vec<String>,
+
+    /// Invert the filters, so that only columns that are not matched by any of the filters are
+    /// written to standard output. When reading JSON Lines input, all top-level fields except
+    /// the given fields are selected.
+    #[clap(short = 'v', long)]
+    invert: bool,
+
+    /// Interpret filters as zero-based, so that the first column is denoted by 0 rather than 1.
+    #[clap(long, conflicts_with = "python-slices")]
+    zero_based: bool,
+
+    /// Interpret filters as Python slices, which are zero-based with an exclusive end, and where
+    /// negative indexes count backwards from the last column, such as `-3:` for the last three
+    /// columns. Ranges whose end comes before their start select nothing, rather than selecting
+    /// columns in reverse order. Only applies to text input.
+    #[clap(long)]
+    python_slices: bool,
 
     /// Optional delimiter to use for splitting input text into columns. If no delimiter is provided, the
-    /// default behaviour will be to split by any amount of whitespace.
-    #[clap(short, long)]
-    delimiter: Option<String>,
+    /// default behaviour will be to split by any amount of whitespace. Multiple delimiters can be
+    /// provided by repeating this option, in which case input text is split on any of them. A
+    /// delimiter of the form [abc] splits on any of the characters within the brackets. When
+    /// reading JSON Lines input, the delimiter is used to separate the selected fields in the
+    /// output instead, using the first of multiple delimiters.
+    #[clap(short, long, multiple_occurrences = true, number_of_values = 1)]
+    delimiter: Vec<String>,
+
+    /// Optional regular expression to use for splitting input text into columns, such as ' {2,}'
+    /// for splitting by two or more spaces while keeping single spaces within columns.
+    #[clap(long, conflicts_with = "delimiter")]
+    delimiter_regex: Option<String>,
+
+    /// Split input text as tab-separated values, as exported by PostgreSQL COPY or MySQL dumps.
+    /// Empty columns are preserved, and the \t, \n, \r and \\ escape sequences within columns are
+    /// decoded. Unless another output format is specified, the selected columns are re-escaped
+    /// and written as tab-separated values.
+    #[clap(long, conflicts_with_all = &["delimiter", "delimiter-regex"])]
+    tsv: bool,
+
+    /// Treat consecutive delimiters as a single delimiter, so that no empty columns are produced.
+    /// This is the default behaviour when splitting by whitespace.
+    #[clap(long, overrides_with = "no-collapse")]
+    collapse: bool,
+
+    /// Preserve empty columns between consecutive delimiters, so that column numbers do not shift
+    /// when a column is blank. This is the default behaviour when splitting by a delimiter.
+    #[clap(long, overrides_with = "collapse")]
+    no_collapse: bool,
+
+    /// Remove leading whitespace from each column after splitting, as well as the trailing
+    /// whitespace that is removed by default, except in TSV mode.
+    #[clap(long)]
+    trim: bool,
+
+    /// Format of the input text. The text format splits each line into columns, while the jsonl
+    /// format parses each line as a JSON object and treats its fields as columns.
+    #[clap(short, long, arg_enum, default_value = "text")]
+    input: InputFormat,
+
+    /// Format to use when writing the selected columns to standard output. The plain format
+    /// separates columns by whitespace, the json format writes one JSON object per line, and the
+    /// json-array format writes a single JSON array containing an array for each line. The
+    /// markdown and html formats render the selected columns as a table. If no format is
+    /// provided, the default behaviour will be to use the plain format, or the tsv format when
+    /// reading tab-separated values.
+    #[clap(short, long, arg_enum)]
+    output: Option<OutputFormat>,
+
+    /// Treat the first line of the input text as a header row. When writing JSON objects, the
+    /// header names are used as keys instead of column numbers. When writing tables, the header
+    /// names are used for the header row.
+    #[clap(long)]
+    header: bool,
+
+    /// Write columns that look like numbers or booleans as unquoted JSON values, instead of
+    /// strings. Only applies to the json and json-array output formats.
+    #[clap(long)]
+    infer_types: bool,
+
+    /// Policy for lines that are missing one or more of the requested columns. The skip policy
+    /// leaves out the missing columns, the empty policy writes an empty column in their place,
+    /// the fill:VALUE policy writes the given value in their place, and the error policy fails
+    /// with the number of the offending line. Only applies to text input.
+    #[clap(long, default_value = "skip")]
+    missing: Missing,
+
+    /// Fail on the first line that is missing one or more of the requested columns. This is
+    /// equivalent to --missing=error.
+    #[clap(long, conflicts_with = "missing")]
+    strict: bool,
+
+    /// Separate input and output lines by NUL instead of a newline, for use with commands such as
+    /// `find -print0` and `xargs -0`.
+    #[clap(short = 'z', long, conflicts_with = "record-separator")]
+    null_data: bool,
+
+    /// Separator between input and output lines, which may contain the escape sequences \0, \n,
+    /// \r, \t, \\ and \xHH. If no separator is provided, the default behaviour will be to
+    /// separate lines by a newline. Only the plain, tsv and json output formats use the
+    /// separator when writing.
+    #[clap(long)]
+    record_separator: Option<String>,
+
+    /// Line ending to use when writing lines. The lf and crlf line endings convert the line
+    /// endings of the output, while the preserve line ending writes the output for each line
+    /// with the line ending it had in the input text.
+    #[clap(
+        long,
+        arg_enum,
+        default_value = "preserve",
+        conflicts_with_all = &["null-data", "record-separator"]
+    )]
+    line_ending: LineEnding,
+
+    /// Policy for terminating the last line written. The always policy terminates every line,
+    /// while the preserve policy leaves the output unterminated if the last line of the input
+    /// text was unterminated.
+    #[clap(long, arg_enum, default_value = "always")]
+    final_newline: FinalNewline,
+
+    /// Preview the first lines of the input text split into numbered columns, and pick which
+    /// columns to select using the keyboard. On exit, the equivalent filters are written to
+    /// standard output, so that they can be used in scripts. Only available when built with
+    /// the interactive feature.
+    #[clap(long, conflicts_with_all = &["filters", "invert"])]
+    interactive: bool,
+
+    /// Instead of slicing, show how the first lines of the input text are split into columns.
+    /// Each column is written with its column number, its header name when used with --header,
+    /// and a * if it would be selected by the filters. Requested columns that are missing from a
+    /// line are also shown. Only applies to text input.
+    #[clap(long, conflicts_with = "interactive")]
+    explain: bool,
+
+    /// Number of lines of the input text to preview when picking columns interactively or
+    /// explaining how input text is split.
+    #[clap(long, default_value = "10")]
+    preview_lines: usize,
+
+    /// Generate shell completions with `completions <shell>`, or a man page with `man`, and
+    /// write them to standard output.
+    #[clap(long, hidden = true, min_values = 1, max_values = 2)]
+    generate: Option<Vec<String>>,
+
+    /// List the names of the presets defined in the config file, for dynamic completion.
+    #[clap(long, hidden = true)]
+    list_presets: bool,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+enum InputFormat {
+    Text,
+    Jsonl,
+}
+
+#[derive(Debug, PartialEq)]
+enum Missing {
+    Skip,
+    Empty,
+    Fill(String),
+    Error,
+}
+
+impl FromStr for Missing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "empty" => Ok(Self::Empty),
+            "error" => Ok(Self::Error),
+            _ => match s.strip_prefix("fill:") {
+                Some(value) => Ok(Self::Fill(value.to_owned())),
+                None => Err(format!(
+                    "invalid policy [{}], expected one of skip, empty, fill:VALUE or error",
+                    s
+                )),
+            },
+        }
+    }
 }
 
 fn main() {
@@ -55,7 +261,22 @@ fn main() {
 }
 
 fn run() -> Result<(), Box<dyn Error>> {
-    let opts: Opts = Opts::parse();
+    let opts: Opts = Opts::parse_from(filter::attach_negative(preset::expand_args(
+        &Opts::into_app(),
+        env::args_os(),
+    )?));
+
+    if let Some(generate) = opts.generate.as_deref() {
+        Generate::parse(generate)?.write(&mut Opts::into_app(), &mut io::stdout())?;
+        return Ok(());
+    }
+
+    if opts.list_presets {
+        for name in Config::load()?.names("colslc") {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
 
     let reader: Box<dyn BufRead> = match opts.path.as_deref() {
         Some("-") => Box::new(BufReader::new(io::stdin())),
@@ -68,12 +289,91 @@ fn run() -> Result<(), Box<dyn Error>> {
     };
     let mut writer = BufWriter::new(io::stdout());
 
+    let indexing = match (opts.zero_based, opts.python_slices) {
+        (_, true) => Indexing::Python,
+        (true, _) => Indexing::ZeroBased,
+        _ => Indexing::OneBased,
+    };
+
+    let (filters, fields) = match opts.input {
+        InputFormat::Text => (filter::parse_filters(&opts.filters, indexing)?, Vec::new()),
+        InputFormat::Jsonl => {
+            let fields = opts
+                .filters
+                .iter()
+                .flat_map(|filter| filter.split(','))
+                .map(|path| path.parse())
+                .collect::<Result<Vec<FieldPath>, _>>()?;
+            (Vec::new(), fields)
+        }
+    };
+
+    let delimiter = match opts.delimiter_regex {
+        _ if !opts.delimiter.is_empty() => Delimiter::any_of(opts.delimiter),
+        Some(pattern) => {
+            let regex = Regex::new(&pattern)
+                .map_err(|err| format!("invalid delimiter regex {}: {}", pattern, err))?;
+            Delimiter::Regex(regex)
+        }
+        None if opts.tsv => Delimiter::Tsv,
+        None => Delimiter::Whitespace,
+    };
+
+    if opts.input == InputFormat::Jsonl && delimiter.separator().is_none() {
+        return Err("--delimiter-regex cannot be used with JSON Lines input".into());
+    }
+
+    let mut splitter = Splitter::new(delimiter);
+    if opts.collapse || opts.no_collapse {
+        splitter.collapse = opts.collapse;
+    }
+    splitter.trim = opts.trim;
+    splitter.terminator = match opts.record_separator.as_deref() {
+        _ if opts.null_data => "\0".to_owned(),
+        Some(separator) => record::parse_separator(separator)?,
+        None => "\n".to_owned(),
+    };
+
+    if opts.interactive {
+        return pick(reader, &splitter, opts.preview_lines, indexing);
+    }
+
+    let output = match opts.output {
+        Some(output) => output,
+        None if opts.tsv => OutputFormat::Tsv,
+        None => OutputFormat::Plain,
+    };
+
+    let mut filters = FilterSet::new(filters).indexing(indexing);
+    if opts.invert {
+        filters = filters.invert();
+    }
+
     let mut slicer = ColSlicer {
         reader,
-        filters: FilterSet::new(opts.filters),
-        delimiter: opts.delimiter,
+        filters,
+        fields,
+        invert: opts.invert,
+        splitter,
+        input: opts.input,
+        output,
+        header: opts.header,
+        infer_types: opts.infer_types,
+        missing: if opts.strict {
+            Missing::Error
+        } else {
+            opts.missing
+        },
+        line_ending: opts.line_ending,
+        final_newline: opts.final_newline,
     };
 
+    if opts.explain {
+        return slicer
+            .explain(&mut writer, opts.preview_lines)
+            .map_err(|err| format!("explain operation failed: {}", err).into());
+    }
+
     slicer
         .slice(&mut writer)
         .map_err(|err| format!("slice operation failed: {}", err))?;
@@ -81,36 +381,186 @@ fn run() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[cfg(feature = "interactive")]
+fn pick<R: BufRead>(
+    mut reader: R,
+    splitter: &Splitter,
+    lines: usize,
+    indexing: Indexing,
+) -> Result<(), Box<dyn Error>> {
+    use crate::interactive::{Outcome, Picker};
+
+    let mut rows = Vec::new();
+    let mut buf = String::new();
+    while rows.len() < lines && reader.read_record(&mut buf, &splitter.terminator)? > 0 {
+        let columns = splitter.split(&buf);
+        rows.push(columns.iter().map(|col| col.to_string()).collect());
+        buf.clear();
+    }
+
+    let mut picker = Picker::new(rows).indexing(indexing);
+    if let Outcome::Accepted(filters) = interactive::run(&mut picker)? {
+        println!("-f {}", filters.join(" "));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "interactive"))]
+fn pick<R: BufRead>(
+    _reader: R,
+    _splitter: &Splitter,
+    _lines: usize,
+    _indexing: Indexing,
+) -> Result<(), Box<dyn Error>> {
+    Err("colslc was built without the interactive feature".into())
+}
+
 struct ColSlicer<R: BufRead> {
     reader: R,
     filters: FilterSet,
-    delimiter: Option<String>,
+    fields: Vec<FieldPath>,
+    invert: bool,
+    splitter: Splitter,
+    input: InputFormat,
+    output: OutputFormat,
+    header: bool,
+    infer_types: bool,
+    missing: Missing,
+    line_ending: LineEnding,
+    final_newline: FinalNewline,
 }
 
 impl<R: BufRead> ColSlicer<R> {
     fn slice<W: Write>(&mut self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        let mut writer = RecordWriter::new(writer, &self.splitter.terminator, self.line_ending);
+        let writer = &mut writer;
+
         let mut buf = String::new();
+        let mut terminated = true;
+        let mut header: Option<Vec<(u32, String)>> = None;
+        let mut line = 0;
+
+        let separator = match (self.input, &self.splitter.delimiter) {
+            (InputFormat::Jsonl, delimiter) => delimiter.separator().unwrap_or(" "),
+            _ => " ",
+        };
+        let mut formatter =
+            self.output
+                .formatter(separator, &self.splitter.terminator, self.infer_types);
 
         loop {
-            match self.reader.read_line(&mut buf) {
+            match self.reader.read_record(&mut buf, &self.splitter.terminator) {
                 Ok(0) => break,
                 Ok(_) => {
-                    if self.filters.is_empty() {
-                        write!(writer, "{}", buf)?;
-                    } else {
-                        let columns: Vec<&str> = match &self.delimiter {
-                            Some(d) => buf.split(d).collect(),
-                            None => buf.split_whitespace().collect(),
-                        };
-
-                        let extracted: Vec<&str> = columns
-                            .into_iter()
-                            .enumerate()
-                            .filter(|&(index, _)| self.filters.apply(1 + index as u32))
-                            .map(|(_, col)| col.trim_end())
-                            .collect();
-
-                        writeln!(writer, "{}", extracted.join(" "))?;
+                    line += 1;
+
+                    let (content, terminator) =
+                        record::split_terminator(&buf, &self.splitter.terminator);
+                    writer.preserve(self.line_ending, terminator);
+                    terminated = !terminator.is_empty();
+
+                    match self.input {
+                        InputFormat::Text
+                            if self.filters.is_empty() && self.output == OutputFormat::Plain =>
+                        {
+                            write!(writer, "{}{}", content, self.splitter.terminator)?;
+                        }
+                        InputFormat::Text => {
+                            let columns = self.splitter.split(&buf);
+                            let filters = self.filters.resolve(columns.len() as u32);
+
+                            let mut extracted: Vec<(u32, &str)> = columns
+                                .iter()
+                                .enumerate()
+                                .map(|(index, col)| (1 + index as u32, col.as_ref()))
+                                .filter(|&(index, _)| filters.is_empty() || filters.apply(index))
+                                .collect();
+
+                            let found = columns.len() as u32;
+                            let requested = filters.upper_bound().unwrap_or(0);
+                            for index in (1 + found..=requested).filter(|&i| filters.apply(i)) {
+                                match &self.missing {
+                                    Missing::Skip => break,
+                                    Missing::Empty => extracted.push((index, "")),
+                                    Missing::Fill(value) => extracted.push((index, value)),
+                                    Missing::Error => {
+                                        return Err(format!(
+                                            "line {} is missing column {}, found {} columns",
+                                            line, index, found
+                                        )
+                                        .into())
+                                    }
+                                }
+                            }
+
+                            filters.reverse_ranges(&mut extracted);
+
+                            if self.header && header.is_none() {
+                                let columns: Vec<Column> = extracted
+                                    .iter()
+                                    .map(|&(index, col)| Column::new(index, col))
+                                    .collect();
+                                formatter.header(writer, &columns)?;
+
+                                header = Some(
+                                    extracted
+                                        .iter()
+                                        .map(|&(index, col)| (index, col.to_owned()))
+                                        .collect(),
+                                );
+                            } else {
+                                let columns: Vec<Column> = extracted
+                                    .iter()
+                                    .map(|&(index, value)| Column {
+                                        index,
+                                        name: header
+                                            .iter()
+                                            .flatten()
+                                            .find(|&(i, _)| *i == index)
+                                            .map(|(_, name)| name.as_str()),
+                                        value,
+                                    })
+                                    .collect();
+                                formatter.row(writer, &columns)?;
+                            }
+                        }
+                        InputFormat::Jsonl if self.splitter.strip(&buf).trim().is_empty() => {}
+                        InputFormat::Jsonl => {
+                            let value: Value = serde_json::from_str(self.splitter.strip(&buf))
+                                .map_err(|err| {
+                                    format!("failed to parse JSON on line {}: {}", line, err)
+                                })?;
+
+                            let selected = self.select_fields(&value);
+
+                            if self.header && header.is_none() {
+                                let names: Vec<Column> = selected
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(index, (name, _))| Column::new(1 + index as u32, name))
+                                    .collect();
+                                formatter.header(writer, &names)?;
+
+                                header = Some(
+                                    names
+                                        .iter()
+                                        .map(|col| (col.index, col.value.to_owned()))
+                                        .collect(),
+                                );
+                            }
+
+                            let columns: Vec<Column> = selected
+                                .iter()
+                                .enumerate()
+                                .map(|(index, (name, value))| Column {
+                                    index: 1 + index as u32,
+                                    name: Some(name),
+                                    value,
+                                })
+                                .collect();
+                            formatter.row(writer, &columns)?;
+                        }
                     }
 
                     buf.clear();
@@ -119,30 +569,149 @@ impl<R: BufRead> ColSlicer<R> {
             }
         }
 
+        formatter.finish(writer)?;
+
+        writer.finish(self.final_newline == FinalNewline::Always || terminated)?;
+        Ok(())
+    }
+
+    /// Writes how each of the first lines of the input text is split into columns, using the
+    /// same splitter and filters as when slicing.
+    fn explain<W: Write>(&mut self, writer: &mut W, lines: usize) -> Result<(), Box<dyn Error>> {
+        if self.input != InputFormat::Text {
+            return Err("only text input can be explained".into());
+        }
+
+        let mut buf = String::new();
+        let mut header: Option<Vec<String>> = None;
+        let mut line = 0;
+
+        while line < lines
+            && self
+                .reader
+                .read_record(&mut buf, &self.splitter.terminator)?
+                > 0
+        {
+            line += 1;
+
+            let columns = self.splitter.split(&buf);
+            let filters = self.filters.resolve(columns.len() as u32);
+            let selected = |index: u32| filters.is_empty() || filters.apply(index);
+            let width = columns.len().max(1).to_string().len();
+
+            writeln!(writer, "line {}: {:?}", line, buf)?;
+            for (index, col) in columns.iter().enumerate() {
+                let name = header
+                    .as_ref()
+                    .and_then(|header| header.get(index))
+                    .map(|name| format!(" ({})", name))
+                    .unwrap_or_default();
+                let index = 1 + index as u32;
+                let mark = if selected(index) { "*" } else { " " };
+
+                writeln!(
+                    writer,
+                    "  {} {:>width$}{} {:?}",
+                    mark,
+                    index,
+                    name,
+                    col,
+                    width = width
+                )?;
+            }
+
+            let found = columns.len() as u32;
+            let requested = filters.upper_bound().unwrap_or(0);
+            for index in (1 + found..=requested).filter(|&i| filters.apply(i)) {
+                writeln!(writer, "  * {:>width$} missing", index, width = width)?;
+            }
+
+            if self.header && header.is_none() {
+                header = Some(columns.iter().map(|col| col.to_string()).collect());
+            }
+
+            buf.clear();
+        }
+
         writer.flush()?;
         Ok(())
     }
+
+    fn select_fields(&self, value: &Value) -> Vec<(String, String)> {
+        let mut selected = Vec::new();
+
+        if self.fields.is_empty() || self.invert {
+            let mut value = value.clone();
+            for path in self.fields.iter() {
+                path.remove(&mut value);
+            }
+
+            match value {
+                Value::Object(map) => {
+                    for (key, value) in map {
+                        selected.push((key, json_to_column(&value)));
+                    }
+                }
+                value => selected.push(("1".to_owned(), json_to_column(&value))),
+            }
+        } else {
+            for path in self.fields.iter() {
+                let values = path.select(value);
+                if values.is_empty() {
+                    selected.push((path.name().to_owned(), String::new()));
+                }
+
+                for (name, value) in values {
+                    selected.push((name, json_to_column(value)));
+                }
+            }
+        }
+
+        selected
+    }
+}
+
+fn json_to_column(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use inslice::filter::Filter;
     use std::str::FromStr;
 
     fn testdata() -> File {
         File::open("src/testdata/input.txt").unwrap()
     }
 
+    fn new_slicer(filters: Vec<Filter>) -> ColSlicer<BufReader<File>> {
+        ColSlicer {
+            reader: BufReader::new(testdata()),
+            filters: FilterSet::new(filters),
+            fields: Vec::new(),
+            invert: false,
+            splitter: Splitter::new(Delimiter::Whitespace),
+            input: InputFormat::Text,
+            output: OutputFormat::Plain,
+            header: false,
+            infer_types: false,
+            missing: Missing::Skip,
+            line_ending: LineEnding::Preserve,
+            final_newline: FinalNewline::Always,
+        }
+    }
+
     #[test]
     fn colslc_slice_exact_ok() -> Result<(), Box<dyn Error>> {
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1")?];
-        let mut slicer = ColSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-            delimiter: None,
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY
@@ -162,11 +731,7 @@ traefik
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1")?, Filter::from_str("3")?];
-        let mut slicer = ColSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-            delimiter: None,
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY IMAGE
@@ -186,11 +751,7 @@ traefik 72bfc37343a4
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1:3")?];
-        let mut slicer = ColSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-            delimiter: None,
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY TAG IMAGE
@@ -210,11 +771,7 @@ traefik 2.5 72bfc37343a4
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1:2")?, Filter::from_str("4:5")?];
-        let mut slicer = ColSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-            delimiter: None,
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY TAG ID CREATED
@@ -234,11 +791,7 @@ traefik 2.5 18 months
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1")?, Filter::from_str("3:4")?];
-        let mut slicer = ColSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-            delimiter: None,
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY IMAGE ID
@@ -258,11 +811,7 @@ traefik 72bfc37343a4 18
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("3:")?];
-        let mut slicer = ColSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-            delimiter: None,
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 IMAGE ID CREATED SIZE
@@ -282,11 +831,7 @@ ae192c4d3ada 17 months ago 152MB
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str(":3")?];
-        let mut slicer = ColSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-            delimiter: None,
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY TAG IMAGE
@@ -306,11 +851,7 @@ traefik 2.5 72bfc37343a4
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str(":")?];
-        let mut slicer = ColSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-            delimiter: None,
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY TAG IMAGE ID CREATED SIZE
@@ -324,4 +865,849 @@ traefik 2.5 72bfc37343a4 18 months ago 68.9MB
         assert_eq!(String::from_utf8(writer)?, expected);
         Ok(())
     }
+
+    #[test]
+    fn colslc_slice_json_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.output = OutputFormat::Json;
+
+        let expected = r#"{"1":"REPOSITORY","4":"ID"}
+{"1":"vault","4":"2"}
+{"1":"redis","4":"3"}
+{"1":"postgres","4":"17"}
+{"1":"traefik","4":"18"}
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_json_header_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1:2")?, Filter::from_str("4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.output = OutputFormat::Json;
+        slicer.header = true;
+        slicer.infer_types = true;
+
+        let expected = r#"{"REPOSITORY":"vault","TAG":"1.8.4","ID":2}
+{"REPOSITORY":"redis","TAG":"6.2-alpine","ID":3}
+{"REPOSITORY":"postgres","TAG":"14.0-alpine","ID":17}
+{"REPOSITORY":"traefik","TAG":2.5,"ID":18}
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_json_escape_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/escape.txt")?);
+        slicer.output = OutputFormat::Json;
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+        slicer.infer_types = true;
+
+        let expected = r#"{"1":"say \"hi\"","2":"back\\slash","3":"tab\there","4":true,"5":"007"}
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_json_array_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("6")?];
+        let mut slicer = new_slicer(filters);
+        slicer.output = OutputFormat::JsonArray;
+
+        let expected = r#"[
+["REPOSITORY","SIZE"],
+["vault","ago"],
+["redis","ago"],
+["postgres","ago"],
+["traefik","ago"]
+]
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_jsonl_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/input.jsonl")?);
+        slicer.input = InputFormat::Jsonl;
+        slicer.fields = vec![
+            FieldPath::from_str("level")?,
+            FieldPath::from_str("http.status")?,
+            FieldPath::from_str("tags.1")?,
+        ];
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+
+        let expected = "\
+info,200,api
+warn,404,web
+error,,
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_jsonl_delimiters_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/input.jsonl")?);
+        slicer.input = InputFormat::Jsonl;
+        slicer.fields = vec![FieldPath::from_str("level")?, FieldPath::from_str("msg")?];
+        slicer.splitter = Splitter::new(Delimiter::any_of(vec!["[;,]".to_owned()]));
+
+        let expected = "\
+info;request served
+warn;not found
+error;upstream \"db\" unavailable
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_jsonl_json_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/input.jsonl")?);
+        slicer.input = InputFormat::Jsonl;
+        slicer.fields = vec![FieldPath::from_str("msg")?, FieldPath::from_str("tags.:")?];
+        slicer.output = OutputFormat::Json;
+
+        let expected = r#"{"msg":"request served","tags.1":"api","tags.2":"v2"}
+{"msg":"not found","tags.1":"web"}
+{"msg":"upstream \"db\" unavailable","tags.:":""}
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_markdown_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/escape.txt")?);
+        slicer.output = OutputFormat::Markdown;
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+
+        let expected = r#"| 1 | 2 | 3 | 4 | 5 |
+| --- | --- | --- | --- | --- |
+| say "hi" | back\\slash | tab	here | true | 007 |
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_markdown_header_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1:2")?];
+        let mut slicer = new_slicer(filters);
+        slicer.output = OutputFormat::Markdown;
+        slicer.header = true;
+
+        let expected = "\
+| REPOSITORY | TAG |
+| --- | --- |
+| vault | 1.8.4 |
+| redis | 6.2-alpine |
+| postgres | 14.0-alpine |
+| traefik | 2.5 |
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_html_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/escape.txt")?);
+        slicer.output = OutputFormat::Html;
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+
+        let expected = "\
+<table>
+  <tbody>
+    <tr><td>say &quot;hi&quot;</td></tr>
+  </tbody>
+</table>
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_html_header_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.output = OutputFormat::Html;
+        slicer.header = true;
+
+        let expected = "\
+<table>
+  <thead>
+    <tr><th>REPOSITORY</th><th>IMAGE</th></tr>
+  </thead>
+  <tbody>
+    <tr><td>vault</td><td>dc15db720d79</td></tr>
+    <tr><td>redis</td><td>6960a2858b36</td></tr>
+    <tr><td>postgres</td><td>ae192c4d3ada</td></tr>
+    <tr><td>traefik</td><td>72bfc37343a4</td></tr>
+  </tbody>
+</table>
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_tsv_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:3")?, Filter::from_str("5")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/input.tsv")?);
+        slicer.splitter = Splitter::new(Delimiter::Tsv);
+        slicer.output = OutputFormat::Tsv;
+
+        let expected = concat!(
+            "name\tnotes\tdeleted_at\n",
+            "alice\tline one\\nline two\t\\N\n",
+            "bob\t\\\\\\\\server\\\\share\t2021-10-01\n",
+        );
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_tsv_markdown_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/input.tsv")?);
+        slicer.splitter = Splitter::new(Delimiter::Tsv);
+        slicer.output = OutputFormat::Markdown;
+        slicer.header = true;
+
+        let expected = r#"| name | notes |
+| --- | --- |
+| alice | line one<br>line two |
+| bob | \\\\server\\share |
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_tsv_json_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/input.tsv")?);
+        slicer.splitter = Splitter::new(Delimiter::Tsv);
+        slicer.output = OutputFormat::Json;
+        slicer.header = true;
+
+        let expected = r#"{"notes":"line one\nline two"}
+{"notes":"\\\\server\\share"}
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_regex_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3:4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.splitter = Splitter::new(Delimiter::Regex(Regex::new(" {2,}")?));
+        slicer.output = OutputFormat::Json;
+        slicer.header = true;
+
+        let expected = r#"{"IMAGE ID":"dc15db720d79","CREATED":"2 days ago"}
+{"IMAGE ID":"6960a2858b36","CREATED":"3 days ago"}
+{"IMAGE ID":"ae192c4d3ada","CREATED":"17 months ago"}
+{"IMAGE ID":"72bfc37343a4","CREATED":"18 months ago"}
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_delimiters_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/mixed.txt")?);
+        slicer.splitter = Splitter::new(Delimiter::any_of(vec!["[,;]".to_owned()]));
+
+        let expected = "\
+sku price
+A-100 2.50
+B-200 10.00
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_no_collapse_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str(":")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/blank.txt")?);
+        slicer.splitter.collapse = false;
+        slicer.output = OutputFormat::JsonArray;
+
+        let expected = r#"[
+["","","a","","b"],
+["a,,c"]
+]
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_collapse_trim_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str(":")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/blank.txt")?);
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+        slicer.splitter.collapse = true;
+        slicer.splitter.trim = true;
+        slicer.output = OutputFormat::JsonArray;
+
+        let expected = r#"[
+["a  b"],
+["a","c"]
+]
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_missing_skip_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("3:4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/short.txt")?);
+
+        let expected = "\
+a c d
+e g
+h
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_missing_empty_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("3:4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/short.txt")?);
+        slicer.missing = Missing::Empty;
+        slicer.output = OutputFormat::JsonArray;
+
+        let expected = r#"[
+["a","c","d"],
+["e","g",""],
+["h","",""]
+]
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_missing_fill_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("3:4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/short.txt")?);
+        slicer.missing = Missing::from_str("fill:-")?;
+
+        let expected = "\
+a c d
+e g -
+h - -
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_missing_error_err() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("3:4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/short.txt")?);
+        slicer.missing = Missing::Error;
+
+        let result = slicer.slice(&mut writer);
+        assert_eq!(
+            result.map_err(|err| err.to_string()),
+            Err("line 2 is missing column 4, found 3 columns".to_owned())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_missing_open_range_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/short.txt")?);
+        slicer.missing = Missing::Error;
+
+        let expected = "\
+b c d
+f g
+
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_invert_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3:5")?];
+        let mut slicer = new_slicer(filters);
+        slicer.filters = slicer.filters.invert();
+
+        let expected = "\
+REPOSITORY TAG SIZE
+vault 1.8.4 ago 186MB
+redis 6.2-alpine ago 31.3MB
+postgres 14.0-alpine ago 152MB
+traefik 2.5 ago 68.9MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_range_reversed_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3:1")?];
+        let mut slicer = new_slicer(filters);
+
+        let expected = "\
+IMAGE TAG REPOSITORY
+dc15db720d79 1.8.4 vault
+6960a2858b36 6.2-alpine redis
+ae192c4d3ada 14.0-alpine postgres
+72bfc37343a4 2.5 traefik
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_range_reversed_in_place_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("4:2")?];
+        let mut slicer = new_slicer(filters);
+
+        let expected = "\
+REPOSITORY ID IMAGE TAG
+vault 2 dc15db720d79 1.8.4
+redis 3 6960a2858b36 6.2-alpine
+postgres 17 ae192c4d3ada 14.0-alpine
+traefik 18 72bfc37343a4 2.5
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_null_data_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/null.txt")?);
+        slicer.splitter = Splitter::new(Delimiter::Literal("/".to_owned()));
+        slicer.splitter.terminator = "\0".to_owned();
+
+        let expected = "a b.txt\0with\nnewline\0c.txt\0";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_line_ending_preserve_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/crlf.csv")?);
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+
+        let expected = "SIZE\r\n186MB\r\n31.3MB\r\n";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_final_newline_crlf_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/crlf_unterminated.csv")?);
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+
+        let expected = "SIZE\r\n186MB\r\n31.3MB\r\n";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_line_ending_lf_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/crlf.csv")?);
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+        slicer.output = OutputFormat::Json;
+        slicer.line_ending = LineEnding::Lf;
+
+        let expected = "{\"3\":\"SIZE\"}\n{\"3\":\"186MB\"}\n{\"3\":\"31.3MB\"}\n";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_line_ending_crlf_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?];
+        let mut slicer = new_slicer(filters);
+        slicer.output = OutputFormat::Markdown;
+        slicer.header = true;
+        slicer.line_ending = LineEnding::Crlf;
+
+        let expected = concat!(
+            "| REPOSITORY |\r\n",
+            "| --- |\r\n",
+            "| vault |\r\n",
+            "| redis |\r\n",
+            "| postgres |\r\n",
+            "| traefik |\r\n",
+        );
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_final_newline_preserve_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?];
+        let mut slicer = new_slicer(filters);
+        slicer.final_newline = FinalNewline::Preserve;
+
+        let expected = "\
+REPOSITORY
+vault
+redis
+postgres
+traefik";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_empty_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/empty.txt")?);
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_single_unterminated_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/single.txt")?);
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, "a b c\n");
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_single_unterminated_preserve_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/single.txt")?);
+        slicer.final_newline = FinalNewline::Preserve;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, "b c");
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_blank_lines_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/lines.txt")?);
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, "a\n\n\nb\n");
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_explain_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2")?, Filter::from_str("4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/blank.txt")?);
+        slicer.splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+
+        let expected = concat!(
+            "line 1: \"  a  b\\n\"\n",
+            "    1 \"  a  b\"\n",
+            "  * 2 missing\n",
+            "  * 4 missing\n",
+            "line 2: \"a,,c\\n\"\n",
+            "    1 \"a\"\n",
+            "  * 2 \"\"\n",
+            "    3 \"c\"\n",
+            "  * 4 missing\n",
+        );
+
+        slicer.explain(&mut writer, 10)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_explain_header_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.header = true;
+
+        let expected = concat!(
+            "line 1: \"REPOSITORY   TAG           IMAGE ID       CREATED         SIZE\\n\"\n",
+            "    1 \"REPOSITORY\"\n",
+            "    2 \"TAG\"\n",
+            "  * 3 \"IMAGE\"\n",
+            "    4 \"ID\"\n",
+            "    5 \"CREATED\"\n",
+            "    6 \"SIZE\"\n",
+            "line 2: \"vault        1.8.4         dc15db720d79   2 days ago      186MB\\n\"\n",
+            "    1 (REPOSITORY) \"vault\"\n",
+            "    2 (TAG) \"1.8.4\"\n",
+            "  * 3 (IMAGE) \"dc15db720d79\"\n",
+            "    4 (ID) \"2\"\n",
+            "    5 (CREATED) \"days\"\n",
+            "    6 (SIZE) \"ago\"\n",
+            "    7 \"186MB\"\n",
+        );
+
+        slicer.explain(&mut writer, 2)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_jsonl_invert_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/input.jsonl")?);
+        slicer.input = InputFormat::Jsonl;
+        slicer.fields = vec![FieldPath::from_str("http")?, FieldPath::from_str("tags")?];
+        slicer.invert = true;
+        slicer.output = OutputFormat::Json;
+
+        let expected = r#"{"level":"info","msg":"request served"}
+{"level":"warn","msg":"not found"}
+{"level":"error","msg":"upstream \"db\" unavailable"}
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_jsonl_invert_nested_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/input.jsonl")?);
+        slicer.input = InputFormat::Jsonl;
+        slicer.fields = vec![
+            FieldPath::from_str("http.status")?,
+            FieldPath::from_str("msg")?,
+        ];
+        slicer.invert = true;
+        slicer.output = OutputFormat::Json;
+
+        let expected = r#"{"level":"info","http":"{}","tags":"[\"api\",\"v2\"]"}
+{"level":"warn","http":"{}","tags":"[\"web\"]"}
+{"level":"error"}
+"#;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_slice_python_negative_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![
+            Filter::parse("0", Indexing::Python)?,
+            Filter::parse("-1", Indexing::Python)?,
+        ];
+        let mut slicer = new_slicer(filters);
+        slicer.filters = slicer.filters.indexing(Indexing::Python);
+
+        let expected = "\
+REPOSITORY SIZE
+vault 186MB
+redis 31.3MB
+postgres 152MB
+traefik 68.9MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn colslc_readme_help_ok() -> Result<(), Box<dyn Error>> {
+        let mut help = Vec::new();
+        Opts::into_app().write_long_help(&mut help)?;
+
+        assert!(include_str!("../../../README.md").contains(&String::from_utf8(help)?));
+        Ok(())
+    }
 }
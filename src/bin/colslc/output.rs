@@ -0,0 +1,354 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::io::Write;
+
+use clap::ArgEnum;
+
+use crate::split::escape_tsv;
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Tsv,
+    Json,
+    JsonArray,
+    Markdown,
+    Html,
+}
+
+impl OutputFormat {
+    /// Creates a formatter for this output format. Columns are separated by the given separator
+    /// in the plain format, and lines written by the plain, tsv and json formats are terminated
+    /// by the given terminator.
+    pub fn formatter<W: Write>(
+        self,
+        separator: &str,
+        terminator: &str,
+        infer_types: bool,
+    ) -> Box<dyn Formatter<W>> {
+        match self {
+            Self::Plain => Box::new(Plain {
+                separator: separator.to_owned(),
+                terminator: terminator.to_owned(),
+            }),
+            Self::Tsv => Box::new(Tsv {
+                terminator: terminator.to_owned(),
+            }),
+            Self::Json => Box::new(Json {
+                infer_types,
+                terminator: terminator.to_owned(),
+            }),
+            Self::JsonArray => Box::new(JsonArray {
+                infer_types,
+                rows: 0,
+            }),
+            Self::Markdown => Box::new(Markdown { header: false }),
+            Self::Html => Box::new(Html {
+                started: false,
+                body: false,
+            }),
+        }
+    }
+}
+
+pub struct Column<'a> {
+    pub index: u32,
+    pub name: Option<&'a str>,
+    pub value: &'a str,
+}
+
+impl<'a> Column<'a> {
+    pub fn new(index: u32, value: &'a str) -> Self {
+        Self {
+            index,
+            name: None,
+            value,
+        }
+    }
+
+    /// Returns the name of the column, falling back to its column number when the input has no
+    /// header row.
+    pub fn name(&self) -> Cow<'a, str> {
+        match self.name {
+            Some(name) => Cow::Borrowed(name),
+            None => Cow::Owned(self.index.to_string()),
+        }
+    }
+}
+
+/// A Formatter writes the columns selected from each line of input text in a particular output
+/// format. The header is only written if the input is known to have a header row, and is always
+/// written before any rows.
+pub trait Formatter<W: Write> {
+    fn header(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>>;
+
+    fn row(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>>;
+
+    fn finish(&mut self, writer: &mut W) -> Result<(), Box<dyn Error>>;
+}
+
+struct Plain {
+    separator: String,
+    terminator: String,
+}
+
+impl<W: Write> Formatter<W> for Plain {
+    fn header(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        self.row(writer, columns)
+    }
+
+    fn row(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        let values: Vec<&str> = columns.iter().map(|col| col.value).collect();
+        write!(
+            writer,
+            "{}{}",
+            values.join(&self.separator),
+            self.terminator
+        )?;
+        Ok(())
+    }
+
+    fn finish(&mut self, _writer: &mut W) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+struct Tsv {
+    terminator: String,
+}
+
+impl<W: Write> Formatter<W> for Tsv {
+    fn header(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        self.row(writer, columns)
+    }
+
+    fn row(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        let values: Vec<Cow<str>> = columns.iter().map(|col| escape_tsv(col.value)).collect();
+        write!(writer, "{}{}", values.join("\t"), self.terminator)?;
+        Ok(())
+    }
+
+    fn finish(&mut self, _writer: &mut W) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+struct Json {
+    infer_types: bool,
+    terminator: String,
+}
+
+impl<W: Write> Formatter<W> for Json {
+    fn header(&mut self, _writer: &mut W, _columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn row(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        write!(writer, "{{")?;
+        for (n, col) in columns.iter().enumerate() {
+            if n > 0 {
+                write!(writer, ",")?;
+            }
+
+            serde_json::to_writer(&mut *writer, &col.name())?;
+            write!(writer, ":")?;
+            write_json_value(writer, col.value, self.infer_types)?;
+        }
+        write!(writer, "}}{}", self.terminator)?;
+
+        Ok(())
+    }
+
+    fn finish(&mut self, _writer: &mut W) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+struct JsonArray {
+    infer_types: bool,
+    rows: usize,
+}
+
+impl<W: Write> Formatter<W> for JsonArray {
+    fn header(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        self.row(writer, columns)
+    }
+
+    fn row(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        write!(writer, "{}", if self.rows == 0 { "[\n[" } else { ",\n[" })?;
+        for (n, col) in columns.iter().enumerate() {
+            if n > 0 {
+                write!(writer, ",")?;
+            }
+            write_json_value(writer, col.value, self.infer_types)?;
+        }
+        write!(writer, "]")?;
+
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writeln!(writer, "{}", if self.rows == 0 { "[]" } else { "\n]" })?;
+        Ok(())
+    }
+}
+
+fn write_json_value<W: Write>(
+    writer: &mut W,
+    value: &str,
+    infer_types: bool,
+) -> Result<(), Box<dyn Error>> {
+    if infer_types
+        && (value == "true" || value == "false" || value.parse::<serde_json::Number>().is_ok())
+    {
+        write!(writer, "{}", value)?;
+    } else {
+        serde_json::to_writer(&mut *writer, value)?;
+    }
+
+    Ok(())
+}
+
+struct Markdown {
+    header: bool,
+}
+
+impl<W: Write> Formatter<W> for Markdown {
+    fn header(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        let names: Vec<Cow<str>> = columns
+            .iter()
+            .map(|col| escape_markdown(col.value))
+            .collect();
+        let alignment = vec!["---"; columns.len()];
+
+        writeln!(writer, "| {} |", names.join(" | "))?;
+        writeln!(writer, "| {} |", alignment.join(" | "))?;
+
+        self.header = true;
+        Ok(())
+    }
+
+    fn row(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        // Markdown tables cannot be rendered without a header row, so fall back to using the
+        // column names of the first row when the input has none.
+        if !self.header {
+            let names: Vec<Cow<str>> = columns.iter().map(|col| col.name()).collect();
+            let header: Vec<Column> = names
+                .iter()
+                .zip(columns)
+                .map(|(name, col)| Column::new(col.index, name))
+                .collect();
+            self.header(writer, &header)?;
+        }
+
+        let values: Vec<Cow<str>> = columns
+            .iter()
+            .map(|col| escape_markdown(col.value))
+            .collect();
+        writeln!(writer, "| {} |", values.join(" | "))?;
+
+        Ok(())
+    }
+
+    fn finish(&mut self, _writer: &mut W) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// Escapes a value for a Markdown table cell, where line breaks are written as <br>, since a
+/// table row cannot span multiple lines.
+fn escape_markdown(value: &str) -> Cow<'_, str> {
+    if value.contains(['|', '\\', '\n', '\r']) {
+        Cow::Owned(
+            value
+                .replace('\\', "\\\\")
+                .replace('|', "\\|")
+                .replace("\r\n", "<br>")
+                .replace(['\n', '\r'], "<br>"),
+        )
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+struct Html {
+    started: bool,
+    body: bool,
+}
+
+impl Html {
+    fn start<W: Write>(&mut self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        if !self.started {
+            writeln!(writer, "<table>")?;
+            self.started = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Formatter<W> for Html {
+    fn header(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        self.start(writer)?;
+
+        writeln!(writer, "  <thead>")?;
+        write!(writer, "    <tr>")?;
+        for col in columns {
+            write!(writer, "<th>{}</th>", escape_html(col.value))?;
+        }
+        writeln!(writer, "</tr>")?;
+        writeln!(writer, "  </thead>")?;
+
+        Ok(())
+    }
+
+    fn row(&mut self, writer: &mut W, columns: &[Column]) -> Result<(), Box<dyn Error>> {
+        self.start(writer)?;
+
+        if !self.body {
+            writeln!(writer, "  <tbody>")?;
+            self.body = true;
+        }
+
+        write!(writer, "    <tr>")?;
+        for col in columns {
+            write!(writer, "<td>{}</td>", escape_html(col.value))?;
+        }
+        writeln!(writer, "</tr>")?;
+
+        Ok(())
+    }
+
+    fn finish(&mut self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        if self.body {
+            writeln!(writer, "  </tbody>")?;
+        }
+
+        if self.started {
+            writeln!(writer, "</table>")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn escape_html(value: &str) -> Cow<'_, str> {
+    if !value.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
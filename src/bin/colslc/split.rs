@@ -0,0 +1,323 @@
+use std::borrow::Cow;
+
+use regex::Regex;
+
+use inslice::record;
+
+pub enum Delimiter {
+    Whitespace,
+    Literal(String),
+    AnyOf(Vec<String>),
+    Regex(Regex),
+    Tsv,
+}
+
+impl Delimiter {
+    /// Creates a delimiter from one or more literal delimiters, splitting on any of them. A
+    /// delimiter of the form [abc] is expanded into a set of single-character delimiters.
+    pub fn any_of(delimiters: Vec<String>) -> Self {
+        let mut expanded: Vec<String> = Vec::new();
+        for d in delimiters {
+            match d.strip_prefix('[').and_then(|d| d.strip_suffix(']')) {
+                Some(class) if !class.is_empty() => {
+                    expanded.extend(class.chars().map(String::from));
+                }
+                _ => expanded.push(d),
+            }
+        }
+
+        match expanded.len() {
+            1 => Self::Literal(expanded.remove(0)),
+            _ => Self::AnyOf(expanded.into_iter().filter(|d| !d.is_empty()).collect()),
+        }
+    }
+
+    /// Returns the separator written between columns when joining them with this delimiter,
+    /// which is the first of multiple delimiters, or None for a regex, which has no single
+    /// separator to write.
+    pub fn separator(&self) -> Option<&str> {
+        match self {
+            Self::Whitespace => Some(" "),
+            Self::Literal(d) => Some(d),
+            Self::AnyOf(delimiters) => delimiters.first().map(String::as_str),
+            Self::Regex(_) => None,
+            Self::Tsv => Some("\t"),
+        }
+    }
+}
+
+pub struct Splitter {
+    pub delimiter: Delimiter,
+    pub collapse: bool,
+    pub trim: bool,
+    pub terminator: String,
+}
+
+impl Splitter {
+    /// Creates a splitter using the default behaviour for the given delimiter, which is to
+    /// collapse runs of whitespace when splitting by whitespace, and to preserve empty columns
+    /// otherwise. Lines are assumed to be terminated by a newline.
+    pub fn new(delimiter: Delimiter) -> Self {
+        Self {
+            collapse: matches!(delimiter, Delimiter::Whitespace),
+            delimiter,
+            trim: false,
+            terminator: "\n".to_owned(),
+        }
+    }
+
+    /// Removes the terminator from the end of a line of input text, if present. Lines ending in
+    /// CRLF have both characters removed.
+    pub fn strip<'a>(&self, line: &'a str) -> &'a str {
+        record::split_terminator(line, &self.terminator).0
+    }
+
+    /// Splits a line of input text into columns, after removing its line terminator. When
+    /// collapsing, consecutive delimiters are treated as one, so that no empty columns are
+    /// produced. Trailing whitespace is removed from each column unless in TSV mode, and when
+    /// trimming, leading whitespace is removed as well. In TSV mode, any escape sequences within a
+    /// column are decoded.
+    pub fn split<'a>(&self, line: &'a str) -> Vec<Cow<'a, str>> {
+        let line = self.strip(line);
+        let trim_end = !matches!(self.delimiter, Delimiter::Tsv);
+
+        let columns: Vec<Cow<str>> = match &self.delimiter {
+            Delimiter::Whitespace => line.split(char::is_whitespace).map(Cow::Borrowed).collect(),
+            Delimiter::Literal(d) => line.split(d.as_str()).map(Cow::Borrowed).collect(),
+            Delimiter::AnyOf(delimiters) => split_any(line, delimiters)
+                .into_iter()
+                .map(Cow::Borrowed)
+                .collect(),
+            Delimiter::Regex(regex) => regex.split(line).map(Cow::Borrowed).collect(),
+            Delimiter::Tsv => line.split('\t').map(unescape_tsv).collect(),
+        };
+
+        columns
+            .into_iter()
+            .map(|col| match col {
+                Cow::Borrowed(col) if self.trim => Cow::Borrowed(col.trim()),
+                Cow::Borrowed(col) if trim_end => Cow::Borrowed(col.trim_end()),
+                Cow::Owned(col) if self.trim => Cow::Owned(col.trim().to_owned()),
+                col => col,
+            })
+            .filter(|col| !(self.collapse && col.is_empty()))
+            .collect()
+    }
+}
+
+/// Splits a line on any of the given delimiters, preferring the longest delimiter when more than
+/// one matches at the same position.
+fn split_any<'a>(line: &'a str, delimiters: &[String]) -> Vec<&'a str> {
+    let mut columns = Vec::new();
+    let mut start = 0;
+
+    for (i, _) in line.char_indices() {
+        if i < start {
+            continue;
+        }
+
+        let matched = delimiters
+            .iter()
+            .filter(|d| line[i..].starts_with(d.as_str()))
+            .map(|d| d.len())
+            .max();
+
+        if let Some(len) = matched {
+            columns.push(&line[start..i]);
+            start = i + len;
+        }
+    }
+
+    columns.push(&line[start..]);
+    columns
+}
+
+/// Decodes the `\t`, `\n`, `\r` and `\\` escape sequences used in TSV exports. Any other escape
+/// sequence, such as the `\N` marker for null values, is passed through unchanged.
+fn unescape_tsv(col: &str) -> Cow<'_, str> {
+    if !col.contains('\\') {
+        return Cow::Borrowed(col);
+    }
+
+    let mut unescaped = String::with_capacity(col.len());
+    let mut chars = col.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    Cow::Owned(unescaped)
+}
+
+/// Encodes a column for writing as TSV, reversing the escape sequences decoded when splitting.
+pub fn escape_tsv(col: &str) -> Cow<'_, str> {
+    if col == "\\N" || !col.contains(['\t', '\n', '\r', '\\']) {
+        return Cow::Borrowed(col);
+    }
+
+    let mut escaped = String::with_capacity(col.len());
+    for c in col.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_tsv_empty_columns_ok() {
+        let columns = Splitter::new(Delimiter::Tsv).split("a\t\t\tb\n");
+
+        assert_eq!(columns, vec!["a", "", "", "b"]);
+    }
+
+    #[test]
+    fn split_tsv_escapes_ok() {
+        let columns =
+            Splitter::new(Delimiter::Tsv).split("tab\\there\tline\\nbreak\tback\\\\slash\t\\N\n");
+
+        assert_eq!(
+            columns,
+            vec!["tab\there", "line\nbreak", "back\\slash", "\\N"]
+        );
+    }
+
+    #[test]
+    fn escape_tsv_roundtrip_ok() {
+        let line = "tab\\there\tline\\nbreak\tback\\\\slash\t\\N";
+        let columns: Vec<String> = Splitter::new(Delimiter::Tsv)
+            .split(line)
+            .iter()
+            .map(|col| escape_tsv(col).into_owned())
+            .collect();
+
+        assert_eq!(columns.join("\t"), line);
+    }
+
+    #[test]
+    fn split_regex_ok() -> Result<(), regex::Error> {
+        let columns =
+            Splitter::new(Delimiter::Regex(Regex::new(r"\s*[,;]\s*")?)).split("a , b;c ;d\n");
+
+        assert_eq!(columns, vec!["a", "b", "c", "d"]);
+        Ok(())
+    }
+
+    #[test]
+    fn split_delimiters_ok() {
+        let splitter = Splitter::new(Delimiter::any_of(vec![",".to_owned(), "::".to_owned()]));
+        let columns = splitter.split("a,b::c,,d\n");
+
+        assert_eq!(columns, vec!["a", "b", "c", "", "d"]);
+    }
+
+    #[test]
+    fn split_delimiters_class_ok() {
+        let splitter = Splitter::new(Delimiter::any_of(vec!["[,;|]".to_owned()]));
+        let columns = splitter.split("a,b;c|d\n");
+
+        assert_eq!(columns, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn split_delimiters_longest_ok() {
+        let splitter = Splitter::new(Delimiter::any_of(vec![":".to_owned(), "::".to_owned()]));
+        let columns = splitter.split("a::b:c\n");
+
+        assert_eq!(columns, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn split_terminator_ok() {
+        let mut splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+        splitter.terminator = "\0".to_owned();
+        let columns = splitter.split("a\nb,c\0");
+
+        assert_eq!(columns, vec!["a\nb", "c"]);
+    }
+
+    #[test]
+    fn split_crlf_ok() {
+        let columns = Splitter::new(Delimiter::Literal(",".to_owned())).split("a,b\r\n");
+
+        assert_eq!(columns, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn split_whitespace_collapse_ok() {
+        let columns = Splitter::new(Delimiter::Whitespace).split("  a  b\n");
+
+        assert_eq!(columns, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn split_whitespace_no_collapse_ok() {
+        let mut splitter = Splitter::new(Delimiter::Whitespace);
+        splitter.collapse = false;
+        let columns = splitter.split("  a  b\n");
+
+        assert_eq!(columns, vec!["", "", "a", "", "b"]);
+    }
+
+    #[test]
+    fn split_delimiter_no_collapse_ok() {
+        let columns = Splitter::new(Delimiter::Literal(",".to_owned())).split("a,,c\n");
+
+        assert_eq!(columns, vec!["a", "", "c"]);
+    }
+
+    #[test]
+    fn split_delimiter_collapse_ok() {
+        let mut splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+        splitter.collapse = true;
+        let columns = splitter.split(",a,,c,\n");
+
+        assert_eq!(columns, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn split_delimiter_trim_ok() {
+        let mut splitter = Splitter::new(Delimiter::Literal(",".to_owned()));
+        splitter.trim = true;
+        let columns = splitter.split(" a , b ,c \n");
+
+        assert_eq!(columns, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn split_delimiter_trim_end_ok() {
+        let columns = Splitter::new(Delimiter::Literal(",".to_owned())).split(" a , b ,c \n");
+
+        assert_eq!(columns, vec![" a", " b", "c"]);
+    }
+
+    #[test]
+    fn split_tsv_no_trim_ok() {
+        let columns = Splitter::new(Delimiter::Tsv).split(" a \t b \n");
+
+        assert_eq!(columns, vec![" a ", " b "]);
+    }
+}
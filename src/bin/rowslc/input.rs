@@ -0,0 +1,347 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use crate::paragraph::ParagraphReader;
+use inslice::record::ReadRecord;
+
+/// The input text, which is either a regular file that can be seeked, or a stream that can only
+/// be read from start to end, such as standard input or a pipe.
+pub enum Input {
+    File(File, u64),
+    Stream(Box<dyn BufRead>),
+}
+
+impl Input {
+    pub fn open(path: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let path = match path {
+            Some("-") | None => return Ok(Self::stdin()),
+            Some(path) => path,
+        };
+
+        let file =
+            File::open(path).map_err(|err| format!("failed to open file {}: {}", path, err))?;
+        let metadata = file
+            .metadata()
+            .map_err(|err| format!("failed to read metadata of file {}: {}", path, err))?;
+
+        if metadata.is_file() {
+            Ok(Self::File(file, metadata.len()))
+        } else {
+            Ok(Self::Stream(Box::new(BufReader::new(file))))
+        }
+    }
+
+    fn stdin() -> Self {
+        Self::Stream(Box::new(BufReader::new(io::stdin())))
+    }
+
+    /// Returns the length of the input text in bytes, if it is a regular file.
+    pub fn len(&self) -> Option<u64> {
+        match *self {
+            Self::File(_, len) => Some(len),
+            Self::Stream(_) => None,
+        }
+    }
+
+    pub fn into_reader(self) -> Box<dyn BufRead> {
+        match self {
+            Self::File(file, _) => Box::new(BufReader::new(file)),
+            Self::Stream(reader) => reader,
+        }
+    }
+
+    /// Counts the rows of the input text, which are paragraphs when reading paragraphs, and
+    /// returns a reader for the input text from its start. A regular file is read twice, while
+    /// a stream is read into memory.
+    pub fn count_rows(
+        self,
+        terminator: &str,
+        paragraphs: bool,
+    ) -> io::Result<(u32, Box<dyn BufRead>)> {
+        match self {
+            Self::File(mut file, _) => {
+                let rows = count_rows(BufReader::new(&mut file), terminator, paragraphs)?;
+                file.seek(SeekFrom::Start(0))?;
+                Ok((rows, Box::new(BufReader::new(file))))
+            }
+            Self::Stream(mut reader) => {
+                let mut input = Vec::new();
+                reader.read_to_end(&mut input)?;
+
+                let rows = count_rows(input.as_slice(), terminator, paragraphs)?;
+                Ok((rows, Box::new(Cursor::new(input))))
+            }
+        }
+    }
+
+    /// Returns a reader for the rows found within the given byte ranges of a regular file.
+    /// Each range is snapped to whole rows, such that it starts at the first row starting at or
+    /// after its start, and ends with the last row starting before its end. Overlapping ranges
+    /// are merged, so that no row is read twice.
+    pub fn windows(
+        self,
+        ranges: Vec<Range<u64>>,
+        terminator: &str,
+    ) -> io::Result<Box<dyn BufRead>> {
+        let (file, len) = match self {
+            Self::File(file, len) => (file, len),
+            Self::Stream(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "byte ranges can only be read from a regular file",
+                ))
+            }
+        };
+
+        let mut snapper = Snapper {
+            reader: BufReader::new(file),
+            len,
+            terminator: terminator.as_bytes().to_vec(),
+        };
+
+        let mut windows: Vec<Range<u64>> = Vec::new();
+        for range in ranges {
+            let start = snapper.snap(range.start)?;
+            let end = snapper.snap(range.end)?;
+            if start < end {
+                windows.push(start..end);
+            }
+        }
+
+        windows.sort_by_key(|window| window.start);
+        let mut merged: VecDeque<Range<u64>> = VecDeque::new();
+        for window in windows {
+            match merged.back_mut() {
+                Some(last) if window.start <= last.end => last.end = last.end.max(window.end),
+                _ => merged.push_back(window),
+            }
+        }
+
+        Ok(Box::new(BufReader::new(WindowReader {
+            file: snapper.reader.into_inner(),
+            windows: merged,
+        })))
+    }
+
+    /// Narrows a regular file to the rows overlapping the given byte range, from the row
+    /// containing its start up to the row containing its last byte, which are then read as a
+    /// stream.
+    pub fn window(mut self, range: Range<u64>, terminator: &str) -> io::Result<Self> {
+        let start = match self.len() {
+            Some(len) if range.start < len && range.start < range.end => {
+                self.row_at(range.start, terminator)?.start
+            }
+            _ => range.start,
+        };
+
+        let window = start..range.end;
+        Ok(Self::Stream(self.windows(vec![window], terminator)?))
+    }
+
+    /// Returns the byte range of the row containing the given byte offset of a regular file,
+    /// or of the last row if the offset is past the end of the file.
+    pub fn row_at(&mut self, offset: u64, terminator: &str) -> io::Result<Range<u64>> {
+        let (file, len) = match self {
+            Self::File(file, len) => (file, *len),
+            Self::Stream(_) => return Ok(0..0),
+        };
+
+        if len == 0 {
+            return Ok(0..0);
+        }
+
+        let mut snapper = Snapper {
+            reader: BufReader::new(file),
+            len,
+            terminator: terminator.as_bytes().to_vec(),
+        };
+
+        let start = snapper.snap_back(offset.min(len - 1))?;
+        let end = snapper.snap(start + 1)?;
+        Ok(start..end)
+    }
+}
+
+const BLOCK_SIZE: u64 = 4096;
+
+/// Finds the boundaries between rows in a regular file, where each row starts right after the
+/// terminator of the previous row.
+struct Snapper<R: Read + Seek> {
+    reader: BufReader<R>,
+    len: u64,
+    terminator: Vec<u8>,
+}
+
+impl<R: Read + Seek> Snapper<R> {
+    /// Returns the offset of the first row starting at or after the given offset, or the
+    /// length of the file if there is no such row.
+    fn snap(&mut self, offset: u64) -> io::Result<u64> {
+        if offset == 0 || offset >= self.len {
+            return Ok(offset.min(self.len));
+        }
+
+        // The row before the offset is read until its terminator, starting from before the
+        // offset so that a row starting exactly at the offset is not skipped.
+        let from = offset.saturating_sub(self.terminator.len() as u64);
+        self.reader.seek(SeekFrom::Start(from))?;
+
+        let last = *self.terminator.last().unwrap_or(&b'\n');
+        let mut position = from;
+        let mut buf = Vec::new();
+        loop {
+            let n = self.reader.read_until(last, &mut buf)? as u64;
+            if n == 0 {
+                return Ok(self.len);
+            }
+            position += n;
+            if position >= offset && buf.ends_with(&self.terminator) {
+                return Ok(position);
+            }
+        }
+    }
+
+    /// Returns the offset of the last row starting at or before the given offset, searching
+    /// backwards from it a block at a time.
+    fn snap_back(&mut self, offset: u64) -> io::Result<u64> {
+        let mut from = offset;
+        loop {
+            from = from.saturating_sub(BLOCK_SIZE);
+
+            let mut start = self.snap(from)?;
+            if start <= offset {
+                loop {
+                    let next = self.snap(start + 1)?;
+                    if next > offset {
+                        return Ok(start);
+                    }
+                    start = next;
+                }
+            }
+
+            if from == 0 {
+                return Ok(0);
+            }
+        }
+    }
+}
+
+/// A WindowReader reads the byte ranges of a file in turn, as if they were contiguous.
+struct WindowReader {
+    file: File,
+    windows: VecDeque<Range<u64>>,
+}
+
+impl Read for WindowReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while let Some(window) = self.windows.front_mut() {
+            let remaining = window.end - window.start;
+            if remaining == 0 {
+                self.windows.pop_front();
+                continue;
+            }
+
+            self.file.seek(SeekFrom::Start(window.start))?;
+            let max = remaining.min(buf.len() as u64) as usize;
+            let n = self.file.read(&mut buf[..max])?;
+            if n == 0 {
+                self.windows.pop_front();
+                continue;
+            }
+
+            window.start += n as u64;
+            return Ok(n);
+        }
+
+        Ok(0)
+    }
+}
+
+/// Counts the rows read from the given reader, which are paragraphs when reading paragraphs.
+fn count_rows<R: BufRead>(mut reader: R, terminator: &str, paragraphs: bool) -> io::Result<u32> {
+    let mut paragraphs = if paragraphs {
+        Some(ParagraphReader::new())
+    } else {
+        None
+    };
+    let mut buf = String::new();
+    let mut rows = 0;
+
+    loop {
+        let read = match &mut paragraphs {
+            Some(paragraphs) => paragraphs.read_paragraph(&mut reader, &mut buf, terminator)?,
+            None => reader.read_record(&mut buf, terminator)?,
+        };
+
+        if read == 0 {
+            return Ok(rows);
+        }
+
+        rows += 1;
+        buf.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    fn new_input(text: &str) -> io::Result<Input> {
+        let mut file = tempfile::tempfile()?;
+        file.write_all(text.as_bytes())?;
+        Ok(Input::File(file, text.len() as u64))
+    }
+
+    #[test]
+    fn input_windows_ok() -> io::Result<()> {
+        let input = new_input("aa\nbb\ncc\ndd\nee\n")?;
+
+        let mut windows = input.windows(vec![1..4, 12..15, 3..7], "\n")?;
+        let mut rows = String::new();
+        windows.read_to_string(&mut rows)?;
+
+        assert_eq!(rows, "bb\ncc\nee\n");
+        Ok(())
+    }
+
+    #[test]
+    fn input_row_at_ok() -> io::Result<()> {
+        let mut input = new_input("aa\nbb\ncc")?;
+
+        assert_eq!(input.row_at(0, "\n")?, 0..3);
+        assert_eq!(input.row_at(4, "\n")?, 3..6);
+        assert_eq!(input.row_at(6, "\n")?, 6..8);
+        assert_eq!(input.row_at(20, "\n")?, 6..8);
+        Ok(())
+    }
+
+    #[test]
+    fn input_window_ok() -> io::Result<()> {
+        let input = new_input("aa\nbb\ncc\ndd\n")?;
+
+        let mut rows = String::new();
+        input
+            .window(4..7, "\n")?
+            .into_reader()
+            .read_to_string(&mut rows)?;
+
+        assert_eq!(rows, "bb\ncc\n");
+        Ok(())
+    }
+
+    #[test]
+    fn input_count_rows_stream_ok() -> io::Result<()> {
+        let input = Input::Stream(Box::new("a\nb\nc".as_bytes()));
+
+        let (rows, mut reader) = input.count_rows("\n", false)?;
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+
+        assert_eq!(rows, 3);
+        assert_eq!(text, "a\nb\nc");
+        Ok(())
+    }
+}
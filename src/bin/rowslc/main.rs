@@ -1,11 +1,26 @@
+use std::collections::VecDeque;
+use std::env;
 use std::error::Error;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::io::{self, BufRead, BufWriter, Write};
 use std::process;
 
-use clap::Parser;
+use clap::{IntoApp, Parser};
 
-use inslice::filter::{Filter, FilterSet};
+use inslice::filter::{self, FilterSet, Indexing};
+use inslice::generate::Generate;
+use inslice::preset::{self, Config};
+use inslice::record::{self, FinalNewline, LineEnding, ReadRecord};
+use inslice::size::{ByteRange, ByteSize};
+
+use crate::input::Input;
+use crate::paragraph::ParagraphReader;
+use crate::sample::{Sampler, Sampling};
+use crate::spool::Spool;
+
+mod input;
+mod paragraph;
+mod sample;
+mod spool;
 
 /// A command-line utility for filtering input text by rows and writing them to standard output
 #[derive(Parser)]
@@ -34,12 +49,151 @@ struct Opts {
     ///     
     /// * [:n] - a range-based filter for selecting the first to last (inclusive) rows
     ///
+    /// * [m:n] - a range-based filter where m is greater than n, for selecting the n'th to m'th
+    ///   (inclusive) rows and writing them in reverse order in place of the range
+    ///
+    /// * [-n] - an index counted backwards from the last row, such that -1 is the last row, which
+    ///   can be used as either bound of a range, such as 2:-2 for all but the first and last rows
+    ///
+    /// * [p%] - a percentage of the rows, which can be used as either bound of a range, such as
+    ///   0%:10% for the first tenth of the rows, or on its own for the row at that percentage,
+    ///   such as 50% for the middle row. As percentages are taken of the number of rows, the rows
+    ///   are counted first, reading standard input into memory.
+    ///
     /// Example:
     ///
     /// `rowslc - -f 1 4:6` will result in the 1st, 4th, 5th, and 6th rows of the input text
     /// provided from standard input being written to standard output, separated by a newline.
+    #[clap(short, long, multiple_occurrences = true)]
+    filters: Vec<String>,
+
+    /// Invert the filters, so that only rows that are not matched by any of the filters are
+    /// written to standard output.
+    #[clap(short = 'v', long)]
+    invert: bool,
+
+    /// Interpret filters as zero-based, so that the first row is denoted by 0 rather than 1.
+    #[clap(long, conflicts_with = "python-slices")]
+    zero_based: bool,
+
+    /// Interpret filters as Python slices, which are zero-based with an exclusive end, and where
+    /// negative indexes count backwards from the last row, such as `-3:` for the last three
+    /// rows. Ranges whose end comes before their start select nothing, rather than selecting
+    /// rows in reverse order. As the number of rows must be known to resolve negative indexes,
+    /// the rows are counted first, reading standard input into memory.
+    #[clap(long)]
+    python_slices: bool,
+
+    /// Prefix each row written to standard output with its row number in the input text.
+    #[clap(short = 'n', long)]
+    line_numbers: bool,
+
+    /// Separator to write between each row number and its row, when used with --line-numbers.
+    #[clap(long, default_value = "\t")]
+    number_separator: String,
+
+    /// Minimum width of each row number, which is right-aligned and padded with spaces, when used
+    /// with --line-numbers.
+    #[clap(long, default_value = "6")]
+    number_width: usize,
+
+    /// Number of rows of context to write before and after each selected row. Overlapping
+    /// context is merged, and non-adjacent groups of rows are separated by a line containing --.
+    #[clap(short = 'C', long, default_value = "0")]
+    context: usize,
+
+    /// Number of rows of context to write before each selected row, overriding --context.
+    #[clap(short = 'B', long)]
+    before: Option<usize>,
+
+    /// Number of rows of context to write after each selected row, overriding --context.
+    #[clap(short = 'A', long)]
+    after: Option<usize>,
+
+    /// Write a random sample of exactly this many rows from those selected by the filters, in
+    /// the order in which they appear in the input text. If fewer rows are selected, all of them
+    /// are written.
+    #[clap(long, conflicts_with_all = &["context", "before", "after", "sample-rate"])]
+    sample: Option<usize>,
+
+    /// Write each row selected by the filters with the given probability, between 0 and 1.
+    #[clap(long, conflicts_with_all = &["context", "before", "after"])]
+    sample_rate: Option<f64>,
+
+    /// Seed for the random number generator used when sampling, so that the same sample is
+    /// written each time.
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// Always write the first row of the input text, treating it as a header row that is
+    /// excluded from filtering and sampling.
+    #[clap(long)]
+    header: bool,
+
+    /// Write the selected rows in reverse order, starting from the bottom-most row. The
+    /// selection is buffered in memory, spilling to a temporary file once it exceeds
+    /// --buffer-size.
     #[clap(short, long)]
-    filters: Vec<Filter>,
+    reverse: bool,
+
+    /// Only read the rows within a range of bytes of the input file, given as start:end with
+    /// optional K, M or G suffixes, such as 1G:1G+4M or 1G:+4M for the 4M following the 1st
+    /// gigabyte. The range is extended to whole rows, from the row containing its start up to
+    /// the row containing its last byte, and can be left open as in 1G: to read until the end.
+    /// Filters and row numbers then count rows from the start of the range. The input must be a
+    /// regular file, which is seeked to the start of the range without reading the rows before
+    /// it.
+    #[clap(long)]
+    byte_range: Option<ByteRange>,
+
+    /// Maximum size of the selection held in memory when writing rows in reverse order, with an
+    /// optional K, M or G suffix.
+    #[clap(long, default_value = "64M")]
+    buffer_size: ByteSize,
+
+    /// Separate input and output rows by NUL instead of a newline, for use with commands such as
+    /// `find -print0` and `xargs -0`.
+    #[clap(short = 'z', long, conflicts_with = "record-separator")]
+    null_data: bool,
+
+    /// Separator between input and output rows, which may contain the escape sequences \0, \n,
+    /// \r, \t, \\ and \xHH. If no separator is provided, the default behaviour will be to
+    /// separate rows by a newline.
+    #[clap(long)]
+    record_separator: Option<String>,
+
+    /// Line ending to use when writing rows. The lf and crlf line endings convert the line
+    /// ending of each row, while the preserve line ending writes each row with the line ending
+    /// it had in the input text.
+    #[clap(
+        long,
+        arg_enum,
+        default_value = "preserve",
+        conflicts_with_all = &["null-data", "record-separator"]
+    )]
+    line_ending: LineEnding,
+
+    /// Policy for terminating the last row written. The always policy terminates every row,
+    /// while the preserve policy leaves the last row of the input text unterminated if it was
+    /// unterminated in the input text.
+    #[clap(long, arg_enum, default_value = "always")]
+    final_newline: FinalNewline,
+
+    /// Treat each paragraph of the input text as a single row, where paragraphs are blocks of
+    /// lines separated by one or more blank lines, as with the output of `kubectl describe`.
+    /// Each paragraph is written along with the blank lines that follow it, and blank lines at
+    /// the start of the input text are skipped.
+    #[clap(short, long, conflicts_with_all = &["null-data", "record-separator"])]
+    paragraphs: bool,
+
+    /// Generate shell completions with `completions <shell>`, or a man page with `man`, and
+    /// write them to standard output.
+    #[clap(long, hidden = true, min_values = 1, max_values = 2)]
+    generate: Option<Vec<String>>,
+
+    /// List the names of the presets defined in the config file, for dynamic completion.
+    #[clap(long, hidden = true)]
+    list_presets: bool,
 }
 
 fn main() {
@@ -50,34 +204,150 @@ fn main() {
 }
 
 fn run() -> Result<(), Box<dyn Error>> {
-    let opts: Opts = Opts::parse();
-
-    let reader: Box<dyn BufRead> = match opts.path.as_deref() {
-        Some("-") => Box::new(BufReader::new(io::stdin())),
-        Some(input) => {
-            let file = File::open(input)
-                .map_err(|err| format!("failed to open file {}: {}", input, err))?;
-            Box::new(BufReader::new(file))
+    let opts: Opts = Opts::parse_from(filter::attach_negative(preset::expand_args(
+        &Opts::into_app(),
+        env::args_os(),
+    )?));
+
+    if let Some(generate) = opts.generate.as_deref() {
+        Generate::parse(generate)?.write(&mut Opts::into_app(), &mut io::stdout())?;
+        return Ok(());
+    }
+
+    if opts.list_presets {
+        for name in Config::load()?.names("rowslc") {
+            println!("{}", name);
         }
-        None => Box::new(BufReader::new(io::stdin())),
+        return Ok(());
+    }
+
+    slice_input(opts, &mut BufWriter::new(io::stdout()))
+}
+
+/// Slices the input text given by the options, writing the selected rows to the writer.
+fn slice_input<W: Write>(opts: Opts, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    let mut input = Input::open(opts.path.as_deref())?;
+
+    let numbering = if opts.line_numbers {
+        Some(Numbering {
+            separator: opts.number_separator,
+            width: opts.number_width,
+        })
+    } else {
+        None
+    };
+
+    let sampler = match (opts.sample, opts.sample_rate) {
+        (Some(count), _) => Some(Sampler::new(Sampling::Count(count), opts.seed)),
+        (None, Some(rate)) if (0.0..=1.0).contains(&rate) => {
+            Some(Sampler::new(Sampling::Rate(rate), opts.seed))
+        }
+        (None, Some(rate)) => {
+            return Err(format!("sample rate must be between 0 and 1, got {}", rate).into())
+        }
+        (None, None) => None,
+    };
+
+    let indexing = match (opts.zero_based, opts.python_slices) {
+        (_, true) => Indexing::Python,
+        (true, _) => Indexing::ZeroBased,
+        _ => Indexing::OneBased,
+    };
+    let mut filters =
+        FilterSet::new(filter::parse_filters(&opts.filters, indexing)?).indexing(indexing);
+    if opts.invert {
+        filters = filters.invert();
+    }
+
+    let terminator = match opts.record_separator.as_deref() {
+        _ if opts.null_data => "\0".to_owned(),
+        Some(separator) => record::parse_separator(separator)?,
+        None => "\n".to_owned(),
+    };
+
+    if let Some(range) = opts.byte_range {
+        if input.len().is_none() {
+            return Err("--byte-range can only be used with a regular file".into());
+        }
+        input = input.window(range.start..range.end.unwrap_or(u64::MAX), &terminator)?;
+    }
+
+    let (reader, filters) = resolve_rows(input, filters, &terminator, opts.paragraphs)?;
+
+    let spool = if opts.reverse {
+        Some(Spool::new(opts.buffer_size.0 as usize, &terminator))
+    } else {
+        None
     };
-    let mut writer = BufWriter::new(io::stdout());
 
     let mut slicer = RowSlicer {
         reader,
-        filters: FilterSet::new(opts.filters),
+        filters,
+        numbering,
+        before: opts.before.unwrap_or(opts.context),
+        after: opts.after.unwrap_or(opts.context),
+        sampler,
+        header: opts.header,
+        spool,
+        reversed: None,
+        buffer_size: opts.buffer_size.0 as usize,
+        last_terminator: terminator.clone(),
+        terminator,
+        line_ending: opts.line_ending,
+        final_newline: opts.final_newline,
+        paragraphs: if opts.paragraphs {
+            Some(ParagraphReader::new())
+        } else {
+            None
+        },
     };
 
     slicer
-        .slice(&mut writer)
+        .slice(writer)
         .map_err(|err| format!("slice operation failed: {}", err))?;
 
     Ok(())
 }
 
+/// Returns a reader for the rows of the input text along with the filters to apply to them.
+/// Filters counted from the last row or given as percentages can only be resolved once the
+/// number of rows is known, so the rows are counted first.
+fn resolve_rows(
+    input: Input,
+    filters: FilterSet,
+    terminator: &str,
+    paragraphs: bool,
+) -> Result<(Box<dyn BufRead>, FilterSet), Box<dyn Error>> {
+    if !filters.is_relative() {
+        return Ok((input.into_reader(), filters));
+    }
+
+    let (rows, reader) = input.count_rows(terminator, paragraphs)?;
+    let filters = filters.resolve(rows).into_owned();
+    Ok((reader, filters))
+}
+
 struct RowSlicer<R: BufRead> {
     reader: R,
     filters: FilterSet,
+    numbering: Option<Numbering>,
+    before: usize,
+    after: usize,
+    sampler: Option<Sampler>,
+    header: bool,
+    spool: Option<Spool>,
+    reversed: Option<(u32, Spool)>,
+    buffer_size: usize,
+    terminator: String,
+    last_terminator: String,
+    line_ending: LineEnding,
+    final_newline: FinalNewline,
+    paragraphs: Option<ParagraphReader>,
+}
+
+struct Numbering {
+    separator: String,
+    width: usize,
 }
 
 impl<R: BufRead> RowSlicer<R> {
@@ -85,42 +355,236 @@ impl<R: BufRead> RowSlicer<R> {
         let mut buf = String::new();
         let mut index = 0;
 
+        // Rows that were not selected are kept in a ring buffer until they are either written
+        // as context before a selected row, or pushed out by newer rows.
+        let mut history: VecDeque<(u32, String)> = VecDeque::with_capacity(self.before);
+        let mut remaining = 0;
+        let mut last: Option<u32> = None;
+
         loop {
-            match self.reader.read_line(&mut buf) {
+            let read = match &mut self.paragraphs {
+                Some(paragraphs) => {
+                    paragraphs.read_paragraph(&mut self.reader, &mut buf, &self.terminator)
+                }
+                None => self.reader.read_record(&mut buf, &self.terminator),
+            };
+
+            match read {
                 Ok(0) => break,
                 Ok(_) => {
-                    if self.filters.is_empty() || self.filters.apply(1 + index) {
-                        write!(writer, "{}", buf)?;
+                    index += 1;
+
+                    // An unterminated last row takes the line ending of the rows before it,
+                    // such as CRLF, when preserving line endings.
+                    let (_, terminator) = record::split_terminator(&buf, &self.terminator);
+                    if !terminator.is_empty() && terminator != self.last_terminator {
+                        self.last_terminator = terminator.to_owned();
+                    }
+
+                    if index == 1 && self.header {
+                        self.write_row(writer, &mut last, index, &buf)?;
+                    } else if let Some(sampler) = &mut self.sampler {
+                        let selected = self.filters.is_empty() || self.filters.apply(index);
+                        if selected && sampler.offer(index, &buf) {
+                            self.emit(writer, &mut last, index, &buf)?;
+                        }
+                    } else if self.filters.is_empty() || self.filters.apply(index) {
+                        for (index, row) in history.drain(..) {
+                            self.emit(writer, &mut last, index, &row)?;
+                        }
+
+                        self.emit(writer, &mut last, index, &buf)?;
+                        remaining = self.after;
+                    } else if remaining > 0 {
+                        self.emit(writer, &mut last, index, &buf)?;
+                        remaining -= 1;
+                    } else if self.before > 0 {
+                        if history.len() == self.before {
+                            history.pop_front();
+                        }
+                        history.push_back((index, buf.clone()));
                     }
 
                     buf.clear();
-                    index += 1;
                 }
                 Err(err) => return Err(err.into()),
             }
         }
 
+        if let Some(sampler) = &mut self.sampler {
+            for (index, row) in sampler.finish() {
+                self.emit(writer, &mut last, index, &row)?;
+            }
+        }
+
+        self.flush_reversed(writer, &mut last)?;
+
+        if let Some(spool) = self.spool.take() {
+            spool.drain_reversed(|index, row| self.write_row(writer, &mut last, index, row))?;
+        }
+
         writer.flush()?;
         Ok(())
     }
+
+    /// Writes a selected row, where the rows of a reversed range are held back until the end
+    /// of the range and then written in reverse order in place of the range.
+    fn emit<W: Write>(
+        &mut self,
+        writer: &mut W,
+        last: &mut Option<u32>,
+        index: u32,
+        row: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        if matches!(self.reversed, Some((end, _)) if index > end) {
+            self.flush_reversed(writer, last)?;
+        }
+
+        let end = match self.filters.reversed_range(index) {
+            Some((_, end)) => end,
+            None => return self.output(writer, last, index, row),
+        };
+
+        let (buffer_size, terminator) = (self.buffer_size, &self.terminator);
+        let (_, spool) = self
+            .reversed
+            .get_or_insert_with(|| (end, Spool::new(buffer_size, terminator)));
+        match record::split_terminator(row, &self.terminator) {
+            (_, "") => spool.push(index, &format!("{}{}", row, self.last_terminator))?,
+            _ => spool.push(index, row)?,
+        }
+
+        if index == end {
+            self.flush_reversed(writer, last)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the rows held back for a reversed range, starting from the bottom-most row.
+    fn flush_reversed<W: Write>(
+        &mut self,
+        writer: &mut W,
+        last: &mut Option<u32>,
+    ) -> Result<(), Box<dyn Error>> {
+        match self.reversed.take() {
+            Some((_, spool)) => {
+                spool.drain_reversed(|index, row| self.output(writer, last, index, row))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Writes a row, or adds it to the spool when writing rows in reverse order, where an
+    /// unterminated last row is terminated like the rows before it.
+    fn output<W: Write>(
+        &mut self,
+        writer: &mut W,
+        last: &mut Option<u32>,
+        index: u32,
+        row: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        match &mut self.spool {
+            Some(spool) => match record::split_terminator(row, &self.terminator) {
+                (_, "") => spool.push(index, &format!("{}{}", row, self.last_terminator)),
+                _ => spool.push(index, row),
+            },
+            None => self.write_row(writer, last, index, row),
+        }
+    }
+
+    fn write_row<W: Write>(
+        &self,
+        writer: &mut W,
+        last: &mut Option<u32>,
+        index: u32,
+        row: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        // A row spans multiple lines when reading paragraphs, each of which is terminated
+        // separately.
+        let lines: Vec<(&str, &str)> = row
+            .split_inclusive(self.terminator.as_str())
+            .map(|line| record::split_terminator(line, &self.terminator))
+            .collect();
+
+        let context = self.before > 0 || self.after > 0;
+        if context && matches!(*last, Some(last) if index > last + 1 || last > index + 1) {
+            // The separator takes the line ending of the row that follows it, unless that row
+            // is the unterminated last row of the input text.
+            let separator = match lines.first() {
+                Some(&(_, terminator)) if !terminator.is_empty() => {
+                    self.line_ending.apply(terminator)
+                }
+                _ => self.line_ending.apply(&self.last_terminator),
+            };
+            write!(writer, "--{}", separator)?;
+        }
+
+        if let Some(numbering) = &self.numbering {
+            write!(
+                writer,
+                "{:>width$}{}",
+                index,
+                numbering.separator,
+                width = numbering.width
+            )?;
+        }
+
+        for (line, terminator) in lines {
+            let terminator = match terminator {
+                "" if self.final_newline == FinalNewline::Always => {
+                    self.line_ending.apply(&self.last_terminator)
+                }
+                terminator => self.line_ending.apply(terminator),
+            };
+            write!(writer, "{}{}", line, terminator)?;
+        }
+
+        *last = Some(index);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use inslice::filter::Filter;
+    use std::ffi::OsString;
+    use std::fs::File;
+    use std::io::{BufReader, Cursor, Seek, SeekFrom};
     use std::str::FromStr;
 
     fn testdata() -> File {
         File::open("src/testdata/input.txt").unwrap()
     }
 
+    fn new_slicer(filters: Vec<Filter>) -> RowSlicer<BufReader<File>> {
+        new_slicer_from(BufReader::new(testdata()), filters)
+    }
+
+    fn new_slicer_from<R: BufRead>(reader: R, filters: Vec<Filter>) -> RowSlicer<R> {
+        RowSlicer {
+            reader,
+            filters: FilterSet::new(filters),
+            numbering: None,
+            before: 0,
+            after: 0,
+            sampler: None,
+            header: false,
+            spool: None,
+            reversed: None,
+            buffer_size: 1024,
+            terminator: "\n".to_owned(),
+            last_terminator: "\n".to_owned(),
+            line_ending: LineEnding::Preserve,
+            final_newline: FinalNewline::Always,
+            paragraphs: None,
+        }
+    }
+
     fn execute(filters: Vec<Filter>, expected: &str) -> Result<(), Box<dyn Error>> {
         let mut writer = Vec::new();
 
-        let mut slicer = RowSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-        };
+        let mut slicer = new_slicer(filters);
 
         slicer.slice(&mut writer)?;
         assert_eq!(String::from_utf8(writer)?, expected);
@@ -142,10 +606,7 @@ REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1")?, Filter::from_str("3")?];
-        let mut slicer = RowSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
@@ -162,10 +623,7 @@ redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1:3")?];
-        let mut slicer = RowSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
@@ -183,16 +641,14 @@ redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1:2")?, Filter::from_str("4:5")?];
-        let mut slicer = RowSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
 vault        1.8.4         dc15db720d79   2 days ago      186MB
 postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
-traefik      2.5           72bfc37343a4   18 months ago   68.9MB";
+traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+";
 
         slicer.slice(&mut writer)?;
         assert_eq!(String::from_utf8(writer)?, expected);
@@ -204,10 +660,7 @@ traefik      2.5           72bfc37343a4   18 months ago   68.9MB";
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("1")?, Filter::from_str("3:4")?];
-        let mut slicer = RowSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
@@ -225,15 +678,13 @@ postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str("3:")?];
-        let mut slicer = RowSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
 postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
-traefik      2.5           72bfc37343a4   18 months ago   68.9MB";
+traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+";
 
         slicer.slice(&mut writer)?;
         assert_eq!(String::from_utf8(writer)?, expected);
@@ -245,10 +696,7 @@ traefik      2.5           72bfc37343a4   18 months ago   68.9MB";
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str(":3")?];
-        let mut slicer = RowSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-        };
+        let mut slicer = new_slicer(filters);
 
         let expected = "\
 REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
@@ -266,15 +714,383 @@ redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
         let mut writer = Vec::new();
 
         let filters = vec![Filter::from_str(":")?];
-        let mut slicer = RowSlicer {
-            reader: BufReader::new(testdata()),
-            filters: FilterSet::new(filters),
-        };
+        let mut slicer = new_slicer(filters);
+
+        let expected = "\
+REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
+vault        1.8.4         dc15db720d79   2 days ago      186MB
+redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_line_numbers_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2")?, Filter::from_str("4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.numbering = Some(Numbering {
+            separator: "\t".to_owned(),
+            width: 6,
+        });
+
+        let expected = concat!(
+            "     2\tvault        1.8.4         dc15db720d79   2 days ago      186MB\n",
+            "     4\tpostgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB\n",
+        );
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_line_numbers_separator_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("4:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.numbering = Some(Numbering {
+            separator: ": ".to_owned(),
+            width: 0,
+        });
+
+        let expected = "\
+4: postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+5: traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_invert_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("4:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.filters = slicer.filters.invert();
+
+        let expected = "\
+vault        1.8.4         dc15db720d79   2 days ago      186MB
+redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_context_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.before = 1;
+        slicer.after = 1;
+
+        let expected = "\
+vault        1.8.4         dc15db720d79   2 days ago      186MB
+redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_context_separator_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("5")?];
+        let mut slicer = new_slicer(filters);
+        slicer.after = 1;
+
+        let expected = "\
+REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
+vault        1.8.4         dc15db720d79   2 days ago      186MB
+--
+traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_context_merge_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.numbering = Some(Numbering {
+            separator: ":".to_owned(),
+            width: 0,
+        });
+        slicer.before = 2;
+        slicer.after = 1;
+
+        let expected = "\
+1:REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
+2:vault        1.8.4         dc15db720d79   2 days ago      186MB
+3:redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+4:postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+5:traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_sample_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.sampler = Some(Sampler::new(Sampling::Count(2), Some(42)));
+        slicer.numbering = Some(Numbering {
+            separator: ":".to_owned(),
+            width: 0,
+        });
+
+        slicer.slice(&mut writer)?;
+        let output = String::from_utf8(writer)?;
+        let indices: Vec<u32> = output
+            .lines()
+            .map(|row| row.split(':').next().unwrap().parse())
+            .collect::<Result<_, _>>()?;
+
+        assert_eq!(indices.len(), 2);
+        assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        assert!(indices.iter().all(|index| *index >= 2));
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_sample_header_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.sampler = Some(Sampler::new(Sampling::Rate(0.0), None));
+        slicer.header = true;
+
+        let expected = "\
+REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_range_reversed_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("4:2")?];
+        let mut slicer = new_slicer(filters);
+
+        let expected = "\
+REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+vault        1.8.4         dc15db720d79   2 days ago      186MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_range_reversed_reverse_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("4:2")?];
+        let mut slicer = new_slicer(filters);
+        slicer.spool = Some(Spool::new(1024, "\n"));
 
         let expected = "\
+vault        1.8.4         dc15db720d79   2 days ago      186MB
+redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
 REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_reverse_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.spool = Some(Spool::new(1024, "\n"));
+
+        let expected = "\
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
 vault        1.8.4         dc15db720d79   2 days ago      186MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_reverse_spill_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.spool = Some(Spool::new(64, "\n"));
+        slicer.header = true;
+
+        let expected = "\
+REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
+traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
 redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+vault        1.8.4         dc15db720d79   2 days ago      186MB
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_reverse_context_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("5")?];
+        let mut slicer = new_slicer(filters);
+        slicer.spool = Some(Spool::new(1024, "\n"));
+        slicer.before = 1;
+
+        let expected = "\
+traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+--
+REPOSITORY   TAG           IMAGE ID       CREATED         SIZE
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_null_data_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/null.txt")?);
+        slicer.terminator = "\0".to_owned();
+
+        let expected = "./with\nnewline\0./c.txt\0";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_null_data_reverse_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/null.txt")?);
+        slicer.terminator = "\0".to_owned();
+        slicer.spool = Some(Spool::new(1024, "\0"));
+
+        let expected = "./c.txt\0./with\nnewline\0./a b.txt\0";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_line_ending_preserve_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/crlf.csv")?);
+
+        let expected = "vault,1.8.4,186MB\r\nredis,6.2-alpine,31.3MB\r\n";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_line_ending_lf_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/crlf.csv")?);
+        slicer.line_ending = LineEnding::Lf;
+        slicer.before = 1;
+
+        let expected = "REPOSITORY,TAG,SIZE\nvault,1.8.4,186MB\nredis,6.2-alpine,31.3MB\n";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_line_ending_crlf_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.line_ending = LineEnding::Crlf;
+        slicer.after = 1;
+
+        let expected = concat!(
+            "REPOSITORY   TAG           IMAGE ID       CREATED         SIZE\r\n",
+            "vault        1.8.4         dc15db720d79   2 days ago      186MB\r\n",
+            "redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB\r\n",
+            "postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB\r\n",
+        );
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_final_newline_preserve_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("4:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.final_newline = FinalNewline::Preserve;
+
+        let expected = "\
 postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
 traefik      2.5           72bfc37343a4   18 months ago   68.9MB";
 
@@ -282,4 +1098,232 @@ traefik      2.5           72bfc37343a4   18 months ago   68.9MB";
         assert_eq!(String::from_utf8(writer)?, expected);
         Ok(())
     }
+
+    #[test]
+    fn rowslc_slice_final_newline_crlf_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/crlf_unterminated.csv")?);
+
+        let expected = "vault,1.8.4,186MB\r\nredis,6.2-alpine,31.3MB\r\n";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_empty_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/empty.txt")?);
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_single_unterminated_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/single.txt")?);
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, "a b c\n");
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_single_unterminated_preserve_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let mut slicer = new_slicer(vec![]);
+        slicer.reader = BufReader::new(File::open("src/testdata/single.txt")?);
+        slicer.final_newline = FinalNewline::Preserve;
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, "a b c");
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_blank_lines_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/lines.txt")?);
+        slicer.numbering = Some(Numbering {
+            separator: ":".to_owned(),
+            width: 0,
+        });
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, "2:\n3:\n4:b\n");
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_paragraphs_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("2:3")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/paragraphs.txt")?);
+        slicer.paragraphs = Some(ParagraphReader::new());
+
+        let expected = "\
+Name:         redis
+Namespace:    default
+
+
+Name:         postgres
+Namespace:    default
+
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_paragraphs_numbered_ok() -> Result<(), Box<dyn Error>> {
+        let mut writer = Vec::new();
+
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("4")?];
+        let mut slicer = new_slicer(filters);
+        slicer.reader = BufReader::new(File::open("src/testdata/paragraphs.txt")?);
+        slicer.paragraphs = Some(ParagraphReader::new());
+        slicer.numbering = Some(Numbering {
+            separator: ":".to_owned(),
+            width: 0,
+        });
+
+        let expected = "\
+1:Name:         vault
+Namespace:    secrets
+
+4:Name:         traefik
+Namespace:    ingress
+";
+
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_python_negative_ok() -> Result<(), Box<dyn Error>> {
+        let filters = vec![Filter::parse("-2:", Indexing::Python)?];
+        let (rows, _) = Input::File(testdata(), 0).count_rows("\n", false)?;
+        let mut slicer = new_slicer(vec![]);
+        slicer.filters = FilterSet::new(filters)
+            .indexing(Indexing::Python)
+            .resolve(rows)
+            .into_owned();
+
+        let expected = "\
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+traefik      2.5           72bfc37343a4   18 months ago   68.9MB
+";
+
+        let mut writer = Vec::new();
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_byte_range_ok() -> Result<(), Box<dyn Error>> {
+        let range: ByteRange = "70:+60".parse()?;
+        let len = testdata().metadata()?.len();
+        let input = Input::File(testdata(), len)
+            .window(range.start..range.end.unwrap_or(u64::MAX), "\n")?;
+
+        let mut slicer = new_slicer_from(input.into_reader(), vec![Filter::from_str("2")?]);
+
+        let expected = "\
+redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+";
+
+        let mut writer = Vec::new();
+        slicer.slice(&mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    fn slice_percent(input: Input, filter: &str) -> Result<String, Box<dyn Error>> {
+        let filters = FilterSet::new(vec![Filter::from_str(filter)?]);
+        let (reader, filters) = resolve_rows(input, filters, "\n", false)?;
+
+        let mut slicer = new_slicer_from(reader, vec![]);
+        slicer.filters = filters;
+
+        let mut writer = Vec::new();
+        slicer.slice(&mut writer)?;
+        Ok(String::from_utf8(writer)?)
+    }
+
+    #[test]
+    fn rowslc_slice_percent_file_stream_ok() -> Result<(), Box<dyn Error>> {
+        // Percentages are taken of the number of rows, regardless of their lengths, so that the
+        // same rows are selected from a regular file as from a stream.
+        let rows = format!("x\n{}\nz\nw\n", "y".repeat(30));
+        let file = || -> Result<Input, Box<dyn Error>> {
+            let mut file = tempfile::tempfile()?;
+            file.write_all(rows.as_bytes())?;
+            file.seek(SeekFrom::Start(0))?;
+            Ok(Input::File(file, rows.len() as u64))
+        };
+        let stream = || Input::Stream(Box::new(Cursor::new(rows.clone().into_bytes())));
+
+        assert_eq!(slice_percent(file()?, "50%")?, "z\n");
+        assert_eq!(slice_percent(stream(), "50%")?, "z\n");
+        assert_eq!(
+            slice_percent(file()?, "0%:50%")?,
+            slice_percent(stream(), "0%:50%")?
+        );
+        assert_eq!(slice_percent(file()?, "75%:")?, "w\n");
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_slice_input_invert_from_end_ok() -> Result<(), Box<dyn Error>> {
+        let args = [
+            "rowslc",
+            "src/testdata/input.txt",
+            "-v",
+            "-f",
+            "1",
+            "-f",
+            "-1",
+        ];
+        let opts = Opts::try_parse_from(filter::attach_negative(args.iter().map(OsString::from)))?;
+
+        let expected = "\
+vault        1.8.4         dc15db720d79   2 days ago      186MB
+redis        6.2-alpine    6960a2858b36   3 days ago      31.3MB
+postgres     14.0-alpine   ae192c4d3ada   17 months ago   152MB
+";
+
+        let mut writer = Vec::new();
+        slice_input(opts, &mut writer)?;
+        assert_eq!(String::from_utf8(writer)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn rowslc_readme_help_ok() -> Result<(), Box<dyn Error>> {
+        let mut help = Vec::new();
+        Opts::into_app().write_long_help(&mut help)?;
+
+        assert!(include_str!("../../../README.md").contains(&String::from_utf8(help)?));
+        Ok(())
+    }
 }
@@ -0,0 +1,83 @@
+use std::io::{self, BufRead};
+
+use inslice::record::{self, ReadRecord};
+
+/// A ParagraphReader reads blocks of lines separated by one or more blank lines, where each
+/// block is read along with the blank lines that follow it. Since the end of a block is only
+/// known once the first line of the next block has been read, that line is held until the next
+/// block is read.
+pub struct ParagraphReader {
+    next: String,
+}
+
+impl ParagraphReader {
+    pub fn new() -> Self {
+        Self {
+            next: String::new(),
+        }
+    }
+
+    /// Reads the next paragraph, appending it to buf. Returns the number of bytes appended,
+    /// which is zero once EOF is reached. Blank lines before the first paragraph are skipped.
+    pub fn read_paragraph<R: BufRead>(
+        &mut self,
+        reader: &mut R,
+        buf: &mut String,
+        terminator: &str,
+    ) -> io::Result<usize> {
+        let start = buf.len();
+        let mut line = String::new();
+        let mut separated = false;
+
+        loop {
+            line.clear();
+            if !self.next.is_empty() {
+                std::mem::swap(&mut line, &mut self.next);
+            } else if reader.read_record(&mut line, terminator)? == 0 {
+                break;
+            }
+
+            let blank = record::split_terminator(&line, terminator)
+                .0
+                .trim()
+                .is_empty();
+
+            if blank {
+                if buf.len() > start {
+                    buf.push_str(&line);
+                    separated = true;
+                }
+            } else if separated {
+                std::mem::swap(&mut line, &mut self.next);
+                break;
+            } else {
+                buf.push_str(&line);
+            }
+        }
+
+        Ok(buf.len() - start)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn read_paragraph_ok() -> io::Result<()> {
+        let mut input = "\n\na\nb\n\n \nc\n\nd".as_bytes();
+        let mut reader = ParagraphReader::new();
+
+        let mut paragraphs = Vec::new();
+        loop {
+            let mut buf = String::new();
+            if reader.read_paragraph(&mut input, &mut buf, "\n")? == 0 {
+                break;
+            }
+            paragraphs.push(buf);
+        }
+
+        assert_eq!(paragraphs, vec!["a\nb\n\n \n", "c\n\n", "d"]);
+        Ok(())
+    }
+}
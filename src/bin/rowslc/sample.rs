@@ -0,0 +1,115 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+pub enum Sampling {
+    Count(usize),
+    Rate(f64),
+}
+
+/// A Sampler selects a random subset of the rows offered to it. When sampling an exact number of
+/// rows, reservoir sampling is used so that only the sampled rows are ever held in memory, and
+/// they are returned in the order in which they appeared in the input text.
+pub struct Sampler {
+    sampling: Sampling,
+    rng: ChaCha8Rng,
+    reservoir: Vec<(u32, String)>,
+    seen: usize,
+}
+
+impl Sampler {
+    /// Creates a sampler, seeding it from the given seed so that its output is reproducible, or
+    /// from the operating system if no seed is given.
+    pub fn new(sampling: Sampling, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+
+        Self {
+            sampling,
+            rng,
+            reservoir: Vec::new(),
+            seen: 0,
+        }
+    }
+
+    /// Offers a row to the sampler, returning true if the row should be written immediately.
+    /// When sampling an exact number of rows, rows are kept until the input text has been fully
+    /// read, and this always returns false.
+    pub fn offer(&mut self, index: u32, row: &str) -> bool {
+        match self.sampling {
+            Sampling::Rate(rate) => self.rng.gen::<f64>() < rate,
+            Sampling::Count(count) => {
+                self.seen += 1;
+                if self.reservoir.len() < count {
+                    self.reservoir.push((index, row.to_owned()));
+                } else {
+                    let n = self.rng.gen_range(0..self.seen);
+                    if n < count {
+                        self.reservoir[n] = (index, row.to_owned());
+                    }
+                }
+
+                false
+            }
+        }
+    }
+
+    /// Returns the rows kept by the sampler, ordered by their row number.
+    pub fn finish(&mut self) -> Vec<(u32, String)> {
+        let mut rows: Vec<(u32, String)> = self.reservoir.drain(..).collect();
+        rows.sort_by_key(|(index, _)| *index);
+        rows
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample(sampling: Sampling, seed: u64) -> Vec<u32> {
+        let mut sampler = Sampler::new(sampling, Some(seed));
+
+        let mut sampled: Vec<u32> = (1..=1000)
+            .filter(|index| sampler.offer(*index, &index.to_string()))
+            .collect();
+        sampled.extend(sampler.finish().into_iter().map(|(index, _)| index));
+        sampled
+    }
+
+    #[test]
+    fn sampler_count_ok() {
+        let sampled = sample(Sampling::Count(10), 42);
+
+        assert_eq!(sampled.len(), 10);
+        assert!(sampled.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn sampler_count_exceeds_input_ok() {
+        let sampled = sample(Sampling::Count(2000), 42);
+
+        assert_eq!(sampled, (1..=1000).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn sampler_rate_ok() {
+        let sampled = sample(Sampling::Rate(0.1), 42);
+
+        assert!(sampled.len() > 50 && sampled.len() < 150);
+        assert!(sample(Sampling::Rate(0.0), 42).is_empty());
+        assert_eq!(sample(Sampling::Rate(1.0), 42).len(), 1000);
+    }
+
+    #[test]
+    fn sampler_seed_reproducible_ok() {
+        assert_eq!(
+            sample(Sampling::Count(10), 7),
+            sample(Sampling::Count(10), 7)
+        );
+        assert_eq!(
+            sample(Sampling::Rate(0.1), 7),
+            sample(Sampling::Rate(0.1), 7)
+        );
+    }
+}
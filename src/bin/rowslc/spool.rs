@@ -0,0 +1,178 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// A Spool collects rows so that they can be written in reverse order once the input text has
+/// been fully read. Rows are held in memory until their total size exceeds the spool's capacity,
+/// at which point they are spilled to a temporary file as a single chunk. Chunks are read back
+/// one at a time, so no more than roughly the capacity is ever held in memory.
+pub struct Spool {
+    capacity: usize,
+    terminator: String,
+    rows: Vec<(u32, String)>,
+    size: usize,
+    file: Option<File>,
+    chunks: Vec<(u64, usize)>,
+}
+
+impl Spool {
+    pub fn new(capacity: usize, terminator: &str) -> Self {
+        Self {
+            capacity,
+            terminator: terminator.to_owned(),
+            rows: Vec::new(),
+            size: 0,
+            file: None,
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Adds a row to the spool. Rows are always terminated by the spool's terminator, since the
+    /// last row of the input text is no longer last once reversed.
+    pub fn push(&mut self, index: u32, row: &str) -> Result<(), Box<dyn Error>> {
+        let mut row = row.to_owned();
+        if !row.ends_with(self.terminator.as_str()) {
+            row.push_str(&self.terminator);
+        }
+
+        self.size += row.len();
+        self.rows.push((index, row));
+
+        if self.size > self.capacity {
+            self.spill()?;
+        }
+
+        Ok(())
+    }
+
+    fn spill(&mut self) -> Result<(), Box<dyn Error>> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(
+                tempfile::tempfile()
+                    .map_err(|err| format!("failed to create temporary file: {}", err))?,
+            ),
+        };
+
+        let mut chunk = Vec::with_capacity(self.size);
+        // Rows may contain newlines when using other terminators, so each row is prefixed with
+        // its length rather than relying on any delimiter.
+        for (index, row) in self.rows.drain(..) {
+            write!(chunk, "{} {}\n{}", index, row.len(), row)?;
+        }
+
+        let offset = file.seek(SeekFrom::End(0))?;
+        file.write_all(&chunk)?;
+        self.chunks.push((offset, chunk.len()));
+
+        self.size = 0;
+        Ok(())
+    }
+
+    /// Consumes the spool, calling f with each row in the reverse of the order in which they
+    /// were added.
+    pub fn drain_reversed<F>(mut self, mut f: F) -> Result<(), Box<dyn Error>>
+    where
+        F: FnMut(u32, &str) -> Result<(), Box<dyn Error>>,
+    {
+        for (index, row) in self.rows.iter().rev() {
+            f(*index, row)?;
+        }
+
+        if let Some(file) = &mut self.file {
+            for &(offset, len) in self.chunks.iter().rev() {
+                let mut chunk = vec![0; len];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut chunk)?;
+                let chunk = String::from_utf8(chunk)?;
+
+                let mut rows: Vec<(u32, &str)> = Vec::new();
+                let mut rest = chunk.as_str();
+                while !rest.is_empty() {
+                    let (prefix, remainder) =
+                        rest.split_once('\n').ok_or("temporary file is corrupted")?;
+                    let (index, len) = prefix
+                        .split_once(' ')
+                        .ok_or("temporary file is corrupted")?;
+                    let len: usize = len.parse()?;
+                    if remainder.len() < len {
+                        return Err("temporary file is corrupted".into());
+                    }
+
+                    rows.push((index.parse()?, &remainder[..len]));
+                    rest = &remainder[len..];
+                }
+
+                for (index, row) in rows.into_iter().rev() {
+                    f(index, row)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn drain(spool: Spool) -> Result<Vec<(u32, String)>, Box<dyn Error>> {
+        let mut rows = Vec::new();
+        spool.drain_reversed(|index, row| {
+            rows.push((index, row.to_owned()));
+            Ok(())
+        })?;
+        Ok(rows)
+    }
+
+    #[test]
+    fn spool_memory_ok() -> Result<(), Box<dyn Error>> {
+        let mut spool = Spool::new(1024, "\n");
+        spool.push(1, "a\n")?;
+        spool.push(2, "b\n")?;
+        spool.push(3, "c")?;
+
+        assert!(spool.file.is_none());
+        assert_eq!(
+            drain(spool)?,
+            vec![
+                (3, "c\n".to_owned()),
+                (2, "b\n".to_owned()),
+                (1, "a\n".to_owned()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn spool_spill_terminator_ok() -> Result<(), Box<dyn Error>> {
+        let mut spool = Spool::new(4, "\0");
+        spool.push(1, "a\nb\0")?;
+        spool.push(2, "c\n")?;
+
+        assert_eq!(
+            drain(spool)?,
+            vec![(2, "c\n\0".to_owned()), (1, "a\nb\0".to_owned())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn spool_spill_ok() -> Result<(), Box<dyn Error>> {
+        let mut spool = Spool::new(4, "\n");
+        for index in 1..=10 {
+            spool.push(index, &format!("row {}\n", index))?;
+        }
+
+        assert!(spool.file.is_some());
+        assert_eq!(
+            drain(spool)?,
+            (1..=10)
+                .rev()
+                .map(|index| (index, format!("row {}\n", index)))
+                .collect::<Vec<(u32, String)>>()
+        );
+        Ok(())
+    }
+}
@@ -1,85 +1,473 @@
+use std::borrow::Cow;
 use std::error::Error;
+use std::ffi::OsString;
 use std::fmt;
 use std::num::ParseIntError;
+use std::ops::Range;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq)]
+/// The indexing that filters are written in. One-based filters are the default, while zero-based
+/// filters denote the first index by 0. Python filters follow the semantics of Python slices,
+/// being zero-based with an exclusive end, where negative indexes count backwards from the last.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indexing {
+    OneBased,
+    ZeroBased,
+    Python,
+}
+
+/// A bound of a filter, which is either a one-based index counted from the first index, a
+/// one-based index counted backwards from the last index, such that FromEnd(1) is the last, or
+/// a percentage of the number of indexes, such that Percent(50.0) is the middle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    Index(u32),
+    FromEnd(u32),
+    Percent(f64),
+}
+
+/// The end of an open-ended range, which selects up to and including the last index.
+const LAST: Bound = Bound::FromEnd(1);
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Filter {
-    start: u32,
-    end: Option<u32>,
+    start: Bound,
+    end: Option<Bound>,
 }
 
 impl FromStr for Filter {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let filter: Vec<&str> = s.split(':').collect();
+        Self::parse(s, Indexing::OneBased)
+    }
+}
+
+impl Filter {
+    /// Parses a filter written in the given indexing.
+    pub fn parse(s: &str, indexing: Indexing) -> Result<Self, ParseError> {
+        parse(s, indexing).map_err(|err| match err {
+            ParseError::ParseIntFailed { err, span, .. } => ParseError::ParseIntFailed {
+                err,
+                span,
+                suggestion: suggest(s, indexing),
+            },
+            err => err,
+        })
+    }
+
+    /// Returns true if this is a range-based filter whose end comes before its start, such as
+    /// `10:1`, which selects the same indexes as `1:10` but in descending order. Bounds of
+    /// different kinds, such as `80%:2`, can only be compared once the filter is resolved.
+    pub fn is_reversed(&self) -> bool {
+        match (self.start, self.end) {
+            (Bound::Percent(start), Some(Bound::Percent(end))) => end < start,
+            _ => matches!(self.range(), Some((start, Some(end))) if end < start),
+        }
+    }
 
-        let start = match filter.get(0) {
-            Some(&"") => 1,
-            Some(&n) => n.parse()?,
-            None => 1,
+    /// Returns true if any bound of the filter is counted from the last index or is a
+    /// percentage, other than the end of an open-ended range, in which case the filter must be
+    /// resolved before use.
+    pub fn is_relative(&self) -> bool {
+        let relative = |bound: Bound| !matches!(bound, Bound::Index(_));
+        relative(self.start) || matches!(self.end, Some(end) if end != LAST && relative(end))
+    }
+
+    /// Resolves bounds counted from the last index and percentages into indexes counted from
+    /// the first, given the number of indexes. The end of an open-ended range is left
+    /// unresolved. As with Python slices, a start before the first index is clamped to it, while
+    /// an end before the first index selects nothing. A percentage selects the indexes that
+    /// fall after it as a start, and those that fall before it as an end, so that `0%:50%` and
+    /// `50%:` split the indexes in two, while an exact percentage selects the index it falls on.
+    fn resolve(&self, len: u32) -> Self {
+        let proportion = |p: f64| (p / 100.0 * f64::from(len)).floor() as u32;
+
+        // A reversed range of percentages selects the same indexes as the range in ascending
+        // order, so each of its bounds is resolved as the other would be.
+        if let (Bound::Percent(start), Some(Bound::Percent(end))) = (self.start, self.end) {
+            if end < start {
+                return Self {
+                    start: Bound::Index(proportion(start)),
+                    end: Some(Bound::Index(proportion(end) + 1)),
+                };
+            }
+        }
+
+        let end = match self.end {
+            None => {
+                let start = match self.start {
+                    Bound::FromEnd(n) if n <= len => Bound::Index(len + 1 - n),
+                    Bound::FromEnd(_) => Bound::Index(0),
+                    Bound::Percent(p) => Bound::Index((proportion(p) + 1).min(len)),
+                    bound => bound,
+                };
+                return Self { start, end: None };
+            }
+            Some(LAST) => LAST,
+            Some(Bound::FromEnd(n)) if n <= len => Bound::Index(len + 1 - n),
+            Some(Bound::FromEnd(_)) => Bound::Index(0),
+            Some(Bound::Percent(p)) => Bound::Index(proportion(p)),
+            Some(bound) => bound,
+        };
+
+        let start = match self.start {
+            Bound::FromEnd(n) if n <= len => Bound::Index(len + 1 - n),
+            Bound::FromEnd(_) => Bound::Index(1),
+            Bound::Percent(p) => Bound::Index(proportion(p) + 1),
+            bound => bound,
         };
 
-        let end = match filter.get(1) {
-            Some(&"") => Some(0),
-            Some(&n) => {
-                let end = n.parse()?;
-                if end < start {
-                    return Err(ParseError::InvalidFilter {
-                        reason: format!("end [{}] cannot be before start [{}]", end, start),
-                    });
-                }
-                Some(end)
+        Self {
+            start,
+            end: Some(end),
+        }
+    }
+
+    /// Returns the first and last indexes selected by the filter, where the last index is None
+    /// for open-ended ranges, or None if the filter has not been resolved.
+    fn range(&self) -> Option<(u32, Option<u32>)> {
+        let start = match self.start {
+            Bound::Index(n) => n,
+            _ => return None,
+        };
+
+        match self.end {
+            None => Some((start, Some(start))),
+            Some(Bound::Index(n)) => Some((start, Some(n))),
+            Some(LAST) => Some((start, None)),
+            Some(_) => None,
+        }
+    }
+}
+
+fn parse(s: &str, indexing: Indexing) -> Result<Filter, ParseError> {
+    let (start, end) = match s.find(':') {
+        Some(n) => (&s[..n], Some((n + 1, &s[n + 1..]))),
+        None => (s, None),
+    };
+
+    let start = match (start, indexing) {
+        ("", _) => Bound::Index(1),
+        (n, _) if n.ends_with('%') => parse_percent(s, 0, n)?,
+        (n, Indexing::OneBased) => parse_bound(s, 0, n)?,
+        (n, Indexing::ZeroBased) => match parse_bound(s, 0, n)? {
+            Bound::Index(n) => Bound::Index(n.saturating_add(1)),
+            bound => bound,
+        },
+        (n, Indexing::Python) => match parse_python_index(s, 0, n)? {
+            Bound::Index(n) => Bound::Index(n.saturating_add(1)),
+            bound => bound,
+        },
+    };
+
+    let end = match (end, indexing) {
+        (Some((_, "")), _) => Some(LAST),
+        (Some((offset, n)), _) if n.ends_with('%') => Some(parse_percent(s, offset, n)?),
+        (Some((offset, n)), Indexing::OneBased) => match parse_bound(s, offset, n)? {
+            Bound::Index(0) => {
+                return Err(ParseError::InvalidFilter {
+                    reason: format!("end [{}] cannot be zero, as indexing starts from one", n),
+                    span: Span::new(s, offset..offset + n.len()),
+                });
             }
-            None => None,
+            bound => Some(bound),
+        },
+        (Some((offset, n)), Indexing::ZeroBased) => match parse_bound(s, offset, n)? {
+            Bound::Index(n) => Some(Bound::Index(n.saturating_add(1))),
+            bound => Some(bound),
+        },
+        // The exclusive end of a zero-based slice is the inclusive end of a one-based range,
+        // so only ends counted from the last index need adjusting.
+        (Some((offset, n)), Indexing::Python) => match parse_python_index(s, offset, n)? {
+            Bound::FromEnd(n) => Some(Bound::FromEnd(n.saturating_add(1))),
+            bound => Some(bound),
+        },
+        (None, _) => None,
+    };
+
+    Ok(Filter { start, end })
+}
+
+/// Parses the index found at the given offset within a filter, pointing at the first character
+/// that is not a digit if it fails to parse, or otherwise at the whole index.
+fn parse_index(s: &str, offset: usize, n: &str) -> Result<u32, ParseError> {
+    n.parse().map_err(|err| {
+        let span = match n.find(|c: char| !c.is_ascii_digit()) {
+            Some(i) => offset + i..offset + i + n[i..].chars().next().map_or(1, char::len_utf8),
+            None => offset..offset + n.len(),
         };
 
-        Ok(Filter { start, end })
+        ParseError::ParseIntFailed {
+            err,
+            span: Span::new(s, span),
+            suggestion: None,
+        }
+    })
+}
+
+/// Parses a percentage found at the given offset within a filter, which may be fractional but
+/// must be between 0% and 100%.
+fn parse_percent(s: &str, offset: usize, n: &str) -> Result<Bound, ParseError> {
+    match n.trim_end_matches('%').parse::<f64>() {
+        Ok(p) if (0.0..=100.0).contains(&p) => Ok(Bound::Percent(p)),
+        _ => Err(ParseError::InvalidFilter {
+            reason: format!("percentage [{}] must be between 0% and 100%", n),
+            span: Span::new(s, offset..offset + n.len()),
+        }),
+    }
+}
+
+/// Parses an index that counts backwards from the last index if negative, such that -1 is the
+/// last, as used by one-based and zero-based filters.
+fn parse_bound(s: &str, offset: usize, n: &str) -> Result<Bound, ParseError> {
+    match n.strip_prefix('-') {
+        Some(m) => match parse_index(s, offset + 1, m)? {
+            0 => Err(ParseError::InvalidFilter {
+                reason: format!("index [{}] cannot be zero, as -1 is the last index", n),
+                span: Span::new(s, offset..offset + n.len()),
+            }),
+            m => Ok(Bound::FromEnd(m)),
+        },
+        None => Ok(Bound::Index(parse_index(s, offset, n)?)),
+    }
+}
+
+/// Parses a zero-based Python index, which counts backwards from the last index if negative.
+fn parse_python_index(s: &str, offset: usize, n: &str) -> Result<Bound, ParseError> {
+    match n.strip_prefix('-') {
+        Some(m) => match parse_index(s, offset + 1, m)? {
+            0 => Ok(Bound::Index(0)),
+            m => Ok(Bound::FromEnd(m)),
+        },
+        None => Ok(Bound::Index(parse_index(s, offset, n)?)),
+    }
+}
+
+/// Suggests a valid filter for one that failed to parse, when it uses a range syntax from
+/// elsewhere, such as `3-` or `1..4`.
+fn suggest(s: &str, indexing: Indexing) -> Option<String> {
+    ["..=", "..", "-"]
+        .iter()
+        .filter(|range| s.contains(*range))
+        .map(|range| s.replacen(range, ":", 1))
+        .find(|filter| parse(filter, indexing).is_ok())
+}
+
+/// Parses each of the filters given, written in the given indexing, tracking the position of
+/// the argument of any filter that fails to parse.
+pub fn parse_filters<S: AsRef<str>>(
+    filters: &[S],
+    indexing: Indexing,
+) -> Result<Vec<Filter>, ParseError> {
+    filters
+        .iter()
+        .enumerate()
+        .map(|(n, filter)| {
+            Filter::parse(filter.as_ref(), indexing).map_err(|mut err| {
+                err.span_mut().argument = Some(1 + n);
+                err
+            })
+        })
+        .collect()
+}
+
+/// Attaches filters with negative indexes, such as `-3:`, to the filters option given before
+/// them, so that they are not mistaken for options themselves. Any filters following them are
+/// given to the filters option again, such that `-f 1 -3: 2` becomes
+/// `-f 1 --filters=-3: --filters 2`, while `-f -3:` becomes `--filters=-3:`. Arguments after
+/// `--` are left as they are.
+pub fn attach_negative<I>(args: I) -> Vec<OsString>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut attached = Vec::new();
+    let mut filters = false;
+    let mut empty = false;
+    let mut reopen = false;
+    let mut escaped = false;
+
+    for arg in args {
+        let text = arg.to_str().unwrap_or_default();
+        if escaped {
+            attached.push(arg);
+            continue;
+        }
+
+        let negative = text
+            .strip_prefix('-')
+            .is_some_and(|text| text.starts_with(|c: char| c.is_ascii_digit()));
+
+        if filters && negative {
+            // The filters option is dropped if it would otherwise be given no filters.
+            if empty {
+                attached.pop();
+            }
+            attached.push(OsString::from(format!("--filters={}", text)));
+            empty = false;
+            reopen = true;
+        } else if filters && !text.starts_with('-') {
+            if reopen {
+                attached.push(OsString::from("--filters"));
+                reopen = false;
+            }
+            attached.push(arg);
+            empty = false;
+        } else {
+            filters = text == "-f" || text == "--filters";
+            empty = filters;
+            escaped = text == "--";
+            reopen = false;
+            attached.push(arg);
+        }
+    }
+
+    attached
+}
+
+/// The location within a filter at which it failed to parse, along with the position of the
+/// filter among the arguments it was given in, if known.
+#[derive(Debug, PartialEq)]
+pub struct Span {
+    pub filter: String,
+    pub range: Range<usize>,
+    pub argument: Option<usize>,
+}
+
+impl Span {
+    pub fn new(filter: &str, range: Range<usize>) -> Self {
+        Self {
+            filter: filter.to_owned(),
+            range,
+            argument: None,
+        }
     }
 }
 
 #[derive(Debug)]
 pub enum ParseError {
-    ParseIntFailed(ParseIntError),
-    InvalidFilter { reason: String },
+    ParseIntFailed {
+        err: ParseIntError,
+        span: Span,
+        suggestion: Option<String>,
+    },
+    InvalidFilter {
+        reason: String,
+        span: Span,
+    },
 }
 
-impl From<ParseIntError> for ParseError {
-    fn from(error: ParseIntError) -> Self {
-        Self::ParseIntFailed(error)
+impl ParseError {
+    pub fn span(&self) -> &Span {
+        match *self {
+            Self::ParseIntFailed { ref span, .. } => span,
+            Self::InvalidFilter { ref span, .. } => span,
+        }
+    }
+
+    fn span_mut(&mut self) -> &mut Span {
+        match *self {
+            Self::ParseIntFailed { ref mut span, .. } => span,
+            Self::InvalidFilter { ref mut span, .. } => span,
+        }
     }
 }
 
 impl Error for ParseError {}
 
+/// Displays the error followed by the offending filter, with carets pointing at the characters
+/// that caused it and a suggested fix, if any:
+///
+/// ```text
+/// failed to parse filter 2: invalid digit found in string
+///     3-
+///      ^ did you mean `3:`?
+/// ```
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let span = self.span();
+        let argument = span.argument.map(|n| format!(" {}", n)).unwrap_or_default();
+
         match *self {
-            Self::ParseIntFailed(ref err) => write!(f, "failed to parse filter: {}", err),
-            Self::InvalidFilter { ref reason } => write!(f, "invalid filter: {}", reason),
+            Self::ParseIntFailed { ref err, .. } => {
+                write!(f, "failed to parse filter{}: {}", argument, err)?
+            }
+            Self::InvalidFilter { ref reason, .. } => {
+                write!(f, "invalid filter{}: {}", argument, reason)?
+            }
+        }
+
+        let start = span.filter.get(..span.range.start).unwrap_or("");
+        let carets = span
+            .filter
+            .get(span.range.clone())
+            .map_or(1, |s| s.chars().count().max(1));
+        write!(
+            f,
+            "\n    {}\n    {}{}",
+            span.filter,
+            " ".repeat(start.chars().count()),
+            "^".repeat(carets)
+        )?;
+
+        if let Self::ParseIntFailed {
+            suggestion: Some(ref suggestion),
+            ..
+        } = *self
+        {
+            write!(f, " did you mean `{}`?", suggestion)?;
         }
+
+        Ok(())
     }
 }
 
-pub struct FilterSet(Vec<Filter>);
+#[derive(Clone, Debug)]
+pub struct FilterSet {
+    filters: Vec<Filter>,
+    inverted: bool,
+    indexing: Indexing,
+}
 
 impl FilterSet {
     pub fn new(filters: Vec<Filter>) -> Self {
-        Self(filters)
+        Self {
+            filters,
+            inverted: false,
+            indexing: Indexing::OneBased,
+        }
+    }
+
+    /// Inverts the filter set, so that it only matches indexes that are not matched by any of
+    /// its filters.
+    pub fn invert(mut self) -> Self {
+        self.inverted = !self.inverted;
+        self
+    }
+
+    /// Sets the indexing that the filters were written in. With Python indexing, ranges whose
+    /// end comes before their start select nothing rather than being reversed.
+    pub fn indexing(mut self, indexing: Indexing) -> Self {
+        self.indexing = indexing;
+        self
     }
 
     pub fn apply(&self, index: u32) -> bool {
-        for filter in self.0.iter() {
-            if index < filter.start {
-                continue;
-            }
+        self.matches(index) != self.inverted
+    }
 
-            if match filter.end {
-                Some(0) => true,
-                Some(n) => index <= n,
-                None => index == filter.start,
-            } {
+    fn matches(&self, index: u32) -> bool {
+        for filter in self.filters.iter() {
+            let (start, end) = match filter.range() {
+                Some((start, Some(end))) if end < start => match self.indexing {
+                    Indexing::Python => continue,
+                    _ => (end, Some(start)),
+                },
+                Some(range) => range,
+                None => continue,
+            };
+
+            if index >= start && end.is_none_or(|end| index <= end) {
                 return true;
             }
         }
@@ -88,11 +476,117 @@ impl FilterSet {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.filters.is_empty()
+    }
+
+    /// Returns true if any of the filters is a reversed range, in which case the selected
+    /// indexes should be written in descending order. A relative filter set should be resolved
+    /// first, since ranges mixing percentages and indexes cannot be compared until then.
+    pub fn is_reversed(&self) -> bool {
+        self.indexing != Indexing::Python && self.filters.iter().any(Filter::is_reversed)
+    }
+
+    /// Returns the lowest and highest index of the reversed range containing the given index,
+    /// merging overlapping reversed ranges into one. The indexes selected within that range
+    /// should be written in descending order in place of the range, while the rest of the
+    /// selection keeps its order.
+    pub fn reversed_range(&self, index: u32) -> Option<(u32, u32)> {
+        if self.inverted || self.indexing == Indexing::Python {
+            return None;
+        }
+
+        let ranges: Vec<(u32, u32)> = self
+            .filters
+            .iter()
+            .filter_map(|filter| match filter.range() {
+                Some((start, Some(end))) if end < start => Some((end, start)),
+                _ => None,
+            })
+            .collect();
+
+        let (mut low, mut high) = ranges
+            .iter()
+            .copied()
+            .find(|&(low, high)| index >= low && index <= high)?;
+
+        loop {
+            let (merged_low, merged_high) = ranges
+                .iter()
+                .filter(|&&(start, end)| start <= high && end >= low)
+                .fold((low, high), |(low, high), &(start, end)| {
+                    (low.min(start), high.max(end))
+                });
+
+            if (merged_low, merged_high) == (low, high) {
+                return Some((low, high));
+            }
+
+            low = merged_low;
+            high = merged_high;
+        }
+    }
+
+    /// Reverses, in place, the runs of selected items that fall within the same reversed
+    /// range. The items must be sorted by ascending index.
+    pub fn reverse_ranges<T>(&self, selected: &mut [(u32, T)]) {
+        let mut start = 0;
+
+        while start < selected.len() {
+            let range = self.reversed_range(selected[start].0);
+            let len = selected[start..]
+                .iter()
+                .take_while(|(index, _)| range.is_some() && self.reversed_range(*index) == range)
+                .count()
+                .max(1);
+
+            selected[start..start + len].reverse();
+            start += len;
+        }
+    }
+
+    /// Returns true if any of the filters has bounds counted from the last index, in which case
+    /// the filter set must be resolved against the number of indexes before it is applied.
+    pub fn is_relative(&self) -> bool {
+        self.filters.iter().any(Filter::is_relative)
+    }
+
+    /// Resolves bounds counted from the last index given the number of indexes, returning a
+    /// resolved copy of the filter set, or the filter set itself if it is not relative.
+    pub fn resolve(&self, len: u32) -> Cow<'_, Self> {
+        if !self.is_relative() {
+            return Cow::Borrowed(self);
+        }
+
+        Cow::Owned(Self {
+            filters: self
+                .filters
+                .iter()
+                .map(|filter| filter.resolve(len))
+                .collect(),
+            inverted: self.inverted,
+            indexing: self.indexing,
+        })
+    }
+
+    /// Returns the largest index explicitly referenced by the filters, ignoring open-ended
+    /// ranges, or None if there is no such index. An inverted filter set has no upper bound.
+    pub fn upper_bound(&self) -> Option<u32> {
+        if self.inverted {
+            return None;
+        }
+
+        self.filters
+            .iter()
+            .filter_map(|filter| match filter.range()? {
+                (_, None) => None,
+                (start, Some(end)) => Some(end.max(start)),
+            })
+            .max()
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
     use super::*;
 
@@ -100,7 +594,7 @@ mod test {
     fn filter_parse_exact_ok() -> Result<(), ParseError> {
         let filter = Filter::from_str("1")?;
         let expected = Filter {
-            start: 1,
+            start: Bound::Index(1),
             end: None,
         };
 
@@ -113,8 +607,8 @@ mod test {
     fn filter_parse_range_ok() -> Result<(), ParseError> {
         let filter = Filter::from_str("2:4")?;
         let expected = Filter {
-            start: 2,
-            end: Some(4),
+            start: Bound::Index(2),
+            end: Some(Bound::Index(4)),
         };
 
         assert_eq!(filter, expected);
@@ -125,8 +619,8 @@ mod test {
     fn filter_parse_range_start_ok() -> Result<(), ParseError> {
         let filter = Filter::from_str("2:")?;
         let expected = Filter {
-            start: 2,
-            end: Some(0),
+            start: Bound::Index(2),
+            end: Some(LAST),
         };
 
         assert_eq!(filter, expected);
@@ -137,8 +631,8 @@ mod test {
     fn filter_parse_range_end_ok() -> Result<(), ParseError> {
         let filter = Filter::from_str(":4")?;
         let expected = Filter {
-            start: 1,
-            end: Some(4),
+            start: Bound::Index(1),
+            end: Some(Bound::Index(4)),
         };
 
         assert_eq!(filter, expected);
@@ -149,8 +643,8 @@ mod test {
     fn filter_parse_range_full_ok() -> Result<(), ParseError> {
         let filter = Filter::from_str(":")?;
         let expected = Filter {
-            start: 1,
-            end: Some(0),
+            start: Bound::Index(1),
+            end: Some(LAST),
         };
 
         assert_eq!(filter, expected);
@@ -159,10 +653,33 @@ mod test {
 
     #[test]
     fn filter_parse_negative_err() -> Result<(), ParseError> {
-        let filter = Filter::from_str("1:-1");
+        let filter = Filter::from_str("1:--1");
+
+        assert!(matches!(filter, Err(ParseError::ParseIntFailed { .. })));
+
+        Ok(())
+    }
 
-        assert!(matches!(filter, Err(ParseError::ParseIntFailed(_))));
+    #[test]
+    fn filter_parse_from_end_ok() -> Result<(), ParseError> {
+        let filter = Filter::from_str("2:-2")?;
+        let expected = Filter {
+            start: Bound::Index(2),
+            end: Some(Bound::FromEnd(2)),
+        };
 
+        assert_eq!(filter, expected);
+        assert_eq!(
+            Filter::parse("-1", Indexing::ZeroBased)?,
+            Filter {
+                start: Bound::FromEnd(1),
+                end: None,
+            }
+        );
+        assert!(matches!(
+            Filter::from_str("-0"),
+            Err(ParseError::InvalidFilter { .. })
+        ));
         Ok(())
     }
 
@@ -170,22 +687,172 @@ mod test {
     fn filter_parse_non_numeric_err() -> Result<(), ParseError> {
         let filter = Filter::from_str("non:numeric");
 
-        assert!(matches!(filter, Err(ParseError::ParseIntFailed(_))));
+        assert!(matches!(filter, Err(ParseError::ParseIntFailed { .. })));
         Ok(())
     }
 
     #[test]
-    fn filter_parse_range_invalid_err() -> Result<(), ParseError> {
-        let filter = Filter::from_str("4:2");
+    fn filter_parse_range_reversed_ok() -> Result<(), ParseError> {
+        let filter = Filter::from_str("4:2")?;
+        let expected = Filter {
+            start: Bound::Index(4),
+            end: Some(Bound::Index(2)),
+        };
+
+        assert_eq!(filter, expected);
+        assert!(filter.is_reversed());
+        Ok(())
+    }
+
+    #[test]
+    fn filter_parse_range_zero_err() -> Result<(), ParseError> {
+        let filter = Filter::from_str("4:0");
 
         assert!(matches!(filter, Err(ParseError::InvalidFilter { .. })));
         Ok(())
     }
 
+    #[test]
+    fn filter_parse_span_err() {
+        let err = Filter::from_str("2:4x").unwrap_err();
+
+        assert_eq!(err.span().range, 3..4);
+        assert_eq!(
+            err.to_string(),
+            "failed to parse filter: invalid digit found in string\n    2:4x\n       ^"
+        );
+    }
+
+    #[test]
+    fn filter_parse_suggestion_err() {
+        let err = Filter::from_str("3-").unwrap_err();
+
+        assert!(matches!(
+            err,
+            ParseError::ParseIntFailed {
+                suggestion: Some(ref suggestion),
+                ..
+            } if suggestion == "3:"
+        ));
+        assert!(err
+            .to_string()
+            .ends_with("\n    3-\n     ^ did you mean `3:`?"));
+    }
+
+    #[test]
+    fn parse_filters_argument_err() {
+        let err = parse_filters(&["1", "2:0"], Indexing::OneBased).unwrap_err();
+
+        assert_eq!(err.span().argument, Some(2));
+        assert!(err
+            .to_string()
+            .starts_with("invalid filter 2: end [0] cannot be zero"));
+    }
+
+    #[test]
+    fn filter_parse_zero_based_ok() -> Result<(), ParseError> {
+        let filter = Filter::parse("0:2", Indexing::ZeroBased)?;
+        let expected = Filter {
+            start: Bound::Index(1),
+            end: Some(Bound::Index(3)),
+        };
+
+        assert_eq!(filter, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn filter_parse_python_ok() -> Result<(), ParseError> {
+        let filter = Filter::parse("1:-1", Indexing::Python)?;
+        let expected = Filter {
+            start: Bound::Index(2),
+            end: Some(Bound::FromEnd(2)),
+        };
+
+        assert_eq!(filter, expected);
+        assert!(filter.is_relative());
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_resolve_python_ok() -> Result<(), ParseError> {
+        let filters = vec![
+            Filter::parse("-2:", Indexing::Python)?,
+            Filter::parse("-9:1", Indexing::Python)?,
+        ];
+        let filters = FilterSet::new(filters).indexing(Indexing::Python);
+        let resolved = filters.resolve(5);
+
+        let selected: Vec<u32> = (1..=5).filter(|&i| resolved.apply(i)).collect();
+        assert_eq!(selected, vec![1, 4, 5]);
+        assert!(!filters.apply(4));
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_apply_python_reversed_false() -> Result<(), ParseError> {
+        let filters = vec![Filter::parse("4:1", Indexing::Python)?];
+        let filters = FilterSet::new(filters).indexing(Indexing::Python);
+
+        assert!(!filters.apply(3));
+        assert!(!filters.is_reversed());
+        Ok(())
+    }
+
+    #[test]
+    fn filter_parse_percent_ok() -> Result<(), ParseError> {
+        let filter = Filter::from_str("0%:12.5%")?;
+        let expected = Filter {
+            start: Bound::Percent(0.0),
+            end: Some(Bound::Percent(12.5)),
+        };
+
+        assert_eq!(filter, expected);
+        assert!(filter.is_relative());
+        Ok(())
+    }
+
+    #[test]
+    fn filter_parse_percent_err() {
+        let filter = Filter::from_str("1:101%");
+
+        assert!(matches!(filter, Err(ParseError::InvalidFilter { .. })));
+    }
+
+    #[test]
+    fn filterset_resolve_percent_ok() -> Result<(), ParseError> {
+        let select = |filter: &str, len: u32| -> Result<Vec<u32>, ParseError> {
+            let filters = FilterSet::new(vec![Filter::from_str(filter)?]);
+            let resolved = filters.resolve(len);
+            Ok((1..=len).filter(|&i| resolved.apply(i)).collect())
+        };
+
+        assert_eq!(select("0%:20%", 10)?, vec![1, 2]);
+        assert_eq!(select("80%:", 10)?, vec![9, 10]);
+        assert_eq!(select("50%", 10)?, vec![6]);
+        assert_eq!(select("100%", 10)?, vec![10]);
+        assert_eq!(select("20%:0%", 10)?, vec![1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_resolve_percent_reversed_ok() -> Result<(), ParseError> {
+        let filters = FilterSet::new(vec![Filter::from_str("80%:2")?]);
+        let resolved = filters.resolve(10);
+
+        assert!(!filters.is_reversed());
+        assert!(resolved.is_reversed());
+        assert_eq!(
+            (1..=10).filter(|&i| resolved.apply(i)).collect::<Vec<_>>(),
+            vec![2, 3, 4, 5, 6, 7, 8, 9]
+        );
+        Ok(())
+    }
+
     #[test]
     fn filterset_apply_exact_true() -> Result<(), ParseError> {
         let filters = vec![Filter {
-            start: 2,
+            start: Bound::Index(2),
             end: None,
         }];
         let index = 2;
@@ -197,7 +864,7 @@ mod test {
     #[test]
     fn filterset_apply_exact_false() -> Result<(), ParseError> {
         let filters = vec![Filter {
-            start: 2,
+            start: Bound::Index(2),
             end: None,
         }];
         let index = 4;
@@ -210,11 +877,11 @@ mod test {
     fn filterset_apply_range_full_true() -> Result<(), ParseError> {
         let filters = vec![
             Filter {
-                start: 1,
-                end: Some(0),
+                start: Bound::Index(1),
+                end: Some(LAST),
             },
             Filter {
-                start: 2,
+                start: Bound::Index(2),
                 end: None,
             },
         ];
@@ -228,11 +895,11 @@ mod test {
     fn filterset_apply_range_inside_true() -> Result<(), ParseError> {
         let filters = vec![
             Filter {
-                start: 1,
-                end: Some(4),
+                start: Bound::Index(1),
+                end: Some(Bound::Index(4)),
             },
             Filter {
-                start: 2,
+                start: Bound::Index(2),
                 end: None,
             },
         ];
@@ -246,11 +913,11 @@ mod test {
     fn filterset_apply_range_inclusive_true() -> Result<(), ParseError> {
         let filters = vec![
             Filter {
-                start: 1,
-                end: Some(4),
+                start: Bound::Index(1),
+                end: Some(Bound::Index(4)),
             },
             Filter {
-                start: 2,
+                start: Bound::Index(2),
                 end: None,
             },
         ];
@@ -264,12 +931,12 @@ mod test {
     fn filterset_apply_range_before_false() -> Result<(), ParseError> {
         let filters = vec![
             Filter {
-                start: 3,
-                end: Some(5),
+                start: Bound::Index(3),
+                end: Some(Bound::Index(5)),
             },
             Filter {
-                start: 2,
-                end: Some(4),
+                start: Bound::Index(2),
+                end: Some(Bound::Index(4)),
             },
         ];
         let index = 1;
@@ -282,12 +949,12 @@ mod test {
     fn filterset_apply_range_after_false() -> Result<(), ParseError> {
         let filters = vec![
             Filter {
-                start: 3,
-                end: Some(5),
+                start: Bound::Index(3),
+                end: Some(Bound::Index(5)),
             },
             Filter {
-                start: 2,
-                end: Some(4),
+                start: Bound::Index(2),
+                end: Some(Bound::Index(4)),
             },
         ];
         let index = 6;
@@ -300,12 +967,12 @@ mod test {
     fn filterset_apply_range_between_false() -> Result<(), ParseError> {
         let filters = vec![
             Filter {
-                start: 4,
-                end: Some(5),
+                start: Bound::Index(4),
+                end: Some(Bound::Index(5)),
             },
             Filter {
-                start: 1,
-                end: Some(2),
+                start: Bound::Index(1),
+                end: Some(Bound::Index(2)),
             },
         ];
         let index = 3;
@@ -313,4 +980,123 @@ mod test {
         assert_eq!(FilterSet::new(filters).apply(index), false);
         Ok(())
     }
+
+    #[test]
+    fn filterset_apply_range_reversed_true() -> Result<(), ParseError> {
+        let filters = vec![Filter {
+            start: Bound::Index(5),
+            end: Some(Bound::Index(2)),
+        }];
+
+        assert!(FilterSet::new(filters).apply(3));
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_apply_range_reversed_false() -> Result<(), ParseError> {
+        let filters = vec![Filter {
+            start: Bound::Index(5),
+            end: Some(Bound::Index(2)),
+        }];
+
+        assert!(!FilterSet::new(filters).apply(6));
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_reversed_range_merged_ok() -> Result<(), ParseError> {
+        let filters = vec![
+            Filter::from_str("1")?,
+            Filter::from_str("5:3")?,
+            Filter::from_str("7:4")?,
+        ];
+        let filters = FilterSet::new(filters);
+
+        assert_eq!(filters.reversed_range(1), None);
+        assert_eq!(filters.reversed_range(3), Some((3, 7)));
+        assert_eq!(filters.reversed_range(7), Some((3, 7)));
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_reverse_ranges_ok() -> Result<(), ParseError> {
+        let filters = vec![Filter::from_str("1")?, Filter::from_str("4:2")?];
+        let mut selected = vec![(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')];
+
+        FilterSet::new(filters).reverse_ranges(&mut selected);
+        assert_eq!(selected, vec![(1, 'a'), (4, 'd'), (3, 'c'), (2, 'b')]);
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_apply_inverted_true() -> Result<(), ParseError> {
+        let filters = vec![
+            Filter {
+                start: Bound::Index(1),
+                end: None,
+            },
+            Filter {
+                start: Bound::Index(4),
+                end: Some(LAST),
+            },
+        ];
+        let index = 3;
+
+        assert!(FilterSet::new(filters).invert().apply(index));
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_apply_inverted_false() -> Result<(), ParseError> {
+        let filters = vec![
+            Filter {
+                start: Bound::Index(1),
+                end: None,
+            },
+            Filter {
+                start: Bound::Index(4),
+                end: Some(LAST),
+            },
+        ];
+        let index = 5;
+
+        assert!(!FilterSet::new(filters).invert().apply(index));
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_upper_bound_some() -> Result<(), ParseError> {
+        let filters = vec![
+            Filter {
+                start: Bound::Index(7),
+                end: None,
+            },
+            Filter {
+                start: Bound::Index(2),
+                end: Some(Bound::Index(9)),
+            },
+            Filter {
+                start: Bound::Index(10),
+                end: Some(Bound::Index(3)),
+            },
+            Filter {
+                start: Bound::Index(12),
+                end: Some(LAST),
+            },
+        ];
+
+        assert_eq!(FilterSet::new(filters).upper_bound(), Some(10));
+        Ok(())
+    }
+
+    #[test]
+    fn filterset_upper_bound_none() -> Result<(), ParseError> {
+        let filters = vec![Filter {
+            start: Bound::Index(3),
+            end: Some(LAST),
+        }];
+
+        assert_eq!(FilterSet::new(filters).upper_bound(), None);
+        Ok(())
+    }
 }
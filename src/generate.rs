@@ -0,0 +1,324 @@
+use std::io::{self, Write};
+
+use clap::{App, ArgSettings};
+use clap_generate::Shell;
+
+/// The documentation that can be generated from the options of a binary, as given to its hidden
+/// --generate option.
+#[derive(Debug, PartialEq)]
+pub enum Generate {
+    Completions(Shell),
+    Man,
+}
+
+impl Generate {
+    /// Parses the values given to --generate, which are either `completions <shell>` or `man`.
+    pub fn parse(values: &[String]) -> Result<Self, String> {
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+
+        match values.as_slice() {
+            ["completions", shell] => shell
+                .parse()
+                .map(Self::Completions)
+                .map_err(|_| format!("unsupported shell [{}] for completions", shell)),
+            ["completions"] => Err("a shell must be specified for completions".to_owned()),
+            ["man"] => Ok(Self::Man),
+            _ => Err(format!(
+                "unknown value [{}] for --generate, expected completions <shell> or man",
+                values.join(" ")
+            )),
+        }
+    }
+
+    /// Writes the generated documentation for the given binary, using the definition of its
+    /// options.
+    pub fn write<W: Write>(&self, app: &mut App, writer: &mut W) -> io::Result<()> {
+        let bin = app.get_name().to_owned();
+
+        match *self {
+            Self::Completions(shell) => {
+                let mut completions = Vec::new();
+                clap_generate::generate(shell, app, &bin, &mut completions);
+                let completions = String::from_utf8_lossy(&completions);
+
+                // The completions for zsh end by calling the generated function, which is
+                // instead called through the function completing preset names.
+                let completions = match shell {
+                    Shell::Zsh => format!(
+                        "{}\n",
+                        completions
+                            .trim_end()
+                            .trim_end_matches(&format!("_{} \"$@\"", bin))
+                            .trim_end()
+                    ),
+                    _ => completions.into_owned(),
+                };
+
+                writer.write_all(completions.as_bytes())?;
+                writer.write_all(preset_completions(shell, &bin).as_bytes())
+            }
+            Self::Man => {
+                app._build_all();
+                writer.write_all(man(app).as_bytes())
+            }
+        }
+    }
+}
+
+/// Returns the completions for preset names, which are appended to the completions generated
+/// for each shell. Since presets are defined in a config file, their names are completed
+/// dynamically by calling the binary with the hidden --list-presets flag. Shells whose
+/// completions cannot easily be extended are left with only the generated completions.
+fn preset_completions(shell: Shell, bin: &str) -> String {
+    let completions = match shell {
+        Shell::Bash => {
+            r#"
+_BIN_presets() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    if [[ "${cur}" == @* ]]; then
+        COMPREPLY=( $(compgen -P @ -W "$(BIN --list-presets 2>/dev/null)" -- "${cur#@}") )
+        return 0
+    fi
+    _BIN "$@"
+}
+
+complete -F _BIN_presets -o bashdefault -o default BIN
+"#
+        }
+        Shell::Fish => {
+            r#"
+complete -c BIN -n 'string match -q -- "@*" (commandline -ct)' -a '(BIN --list-presets 2>/dev/null | string replace -r "^" "@")' -d 'Preset'
+"#
+        }
+        Shell::Zsh => {
+            r#"
+_BIN_presets() {
+    if [[ "${PREFIX}" == @* ]]; then
+        local -a presets
+        presets=(${(f)"$(BIN --list-presets 2>/dev/null)"})
+        compset -P @
+        compadd -P @ -a presets
+    else
+        _BIN "$@"
+    fi
+}
+
+_BIN_presets "$@"
+"#
+        }
+        _ => "",
+    };
+
+    completions.replace("BIN", bin)
+}
+
+/// Renders a man page in roff format from the definition of the options of a binary.
+fn man(app: &App) -> String {
+    let bin = app.get_name();
+    let version = app.render_version();
+
+    let mut page = String::new();
+    page.push_str(&format!(
+        ".TH {} 1 \"\" \"{}\"\n",
+        bin.to_uppercase(),
+        escape(version.trim())
+    ));
+
+    page.push_str(".SH NAME\n");
+    match app.get_about() {
+        Some(about) => page.push_str(&format!("{} \\- {}\n", bin, escape(about))),
+        None => page.push_str(&format!("{}\n", bin)),
+    }
+
+    page.push_str(".SH SYNOPSIS\n");
+    page.push_str(&format!("\\fB{}\\fR [OPTIONS]", bin));
+    for positional in app.get_positionals() {
+        page.push_str(&format!(" [{}]", positional.get_name().to_uppercase()));
+    }
+    page.push('\n');
+
+    if let Some(about) = app.get_long_about() {
+        page.push_str(".SH DESCRIPTION\n");
+        page.push_str(&paragraphs(about));
+    }
+
+    let (positionals, options): (Vec<_>, Vec<_>) = app
+        .get_arguments()
+        .filter(|arg| !arg.is_set(ArgSettings::Hidden))
+        .partition(|arg| arg.get_index().is_some());
+
+    if !positionals.is_empty() {
+        page.push_str(".SH ARGUMENTS\n");
+        for arg in positionals {
+            page.push_str(&format!(".TP\n\\fI{}\\fR\n", arg.get_name().to_uppercase()));
+            page.push_str(&help(arg));
+        }
+    }
+
+    if !options.is_empty() {
+        page.push_str(".SH OPTIONS\n");
+        for arg in options {
+            let mut flags = Vec::new();
+            if let Some(short) = arg.get_short() {
+                flags.push(format!("\\fB\\-{}\\fR", short));
+            }
+            if let Some(long) = arg.get_long() {
+                flags.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+            }
+
+            let mut tag = flags.join(", ");
+            if arg.is_set(ArgSettings::TakesValue) {
+                let names = match arg.get_value_names() {
+                    Some(names) => names.iter().map(|name| name.to_string()).collect(),
+                    None => vec![arg.get_name().to_uppercase()],
+                };
+                for name in names {
+                    tag.push_str(&format!(" <\\fI{}\\fR>", escape(&name)));
+                }
+                if arg.is_set(ArgSettings::MultipleValues) {
+                    tag.push_str("...");
+                }
+            }
+
+            page.push_str(&format!(".TP\n{}\n", tag));
+            page.push_str(&help(arg));
+        }
+    }
+
+    page
+}
+
+fn help(arg: &clap::Arg) -> String {
+    let mut help = paragraphs(
+        arg.get_long_about()
+            .or_else(|| arg.get_about())
+            .unwrap_or(""),
+    );
+
+    let values: Vec<&str> = arg
+        .get_possible_values()
+        .unwrap_or(&[])
+        .iter()
+        .filter(|value| !value.is_hidden())
+        .map(|value| value.get_name())
+        .collect();
+    if !values.is_empty() {
+        help.push_str(&format!(
+            ".IP\n[possible values: {}]\n",
+            escape(&values.join(", "))
+        ));
+    }
+
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| format!("{:?}", value.to_string_lossy()))
+        .collect();
+    if !defaults.is_empty() && !arg.is_set(ArgSettings::HideDefaultValue) {
+        help.push_str(&format!(
+            ".IP\n[default: {}]\n",
+            escape(&defaults.join(", "))
+        ));
+    }
+
+    help
+}
+
+/// Renders blank-line separated paragraphs of text, each as an indented paragraph.
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .enumerate()
+        .map(|(n, paragraph)| {
+            let paragraph = escape(paragraph);
+            match n {
+                0 => format!("{}\n", paragraph),
+                _ => format!(".IP\n{}\n", paragraph),
+            }
+        })
+        .collect()
+}
+
+/// Escapes text for roff, so that backslashes and hyphens are written literally and lines
+/// cannot be mistaken for requests.
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.trim().replace('\\', "\\e").replace('-', "\\-");
+            match line.chars().next() {
+                Some('.') | Some('\'') => format!("\\&{}", line),
+                _ => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn values(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn generate_parse_ok() -> Result<(), String> {
+        assert_eq!(
+            Generate::parse(&values(&["completions", "bash"]))?,
+            Generate::Completions(Shell::Bash)
+        );
+        assert_eq!(Generate::parse(&values(&["man"]))?, Generate::Man);
+        Ok(())
+    }
+
+    #[test]
+    fn generate_parse_err() {
+        assert!(Generate::parse(&values(&["completions"])).is_err());
+        assert!(Generate::parse(&values(&["completions", "tcsh"])).is_err());
+        assert!(Generate::parse(&values(&["markdown"])).is_err());
+    }
+
+    #[test]
+    fn generate_man_ok() -> io::Result<()> {
+        let mut app = App::new("slc")
+            .version("1.0.0")
+            .about("Slices text")
+            .arg(clap::Arg::new("path").about("Path to input file"))
+            .arg(
+                clap::Arg::new("filters")
+                    .short('f')
+                    .long("filters")
+                    .takes_value(true)
+                    .about("Filters to be applied"),
+            )
+            .arg(clap::Arg::new("secret").long("secret").hidden(true));
+
+        let mut page = Vec::new();
+        Generate::Man.write(&mut app, &mut page)?;
+        let page = String::from_utf8(page).unwrap();
+
+        assert!(page.starts_with(".TH SLC 1 \"\" \"slc 1.0.0\"\n"));
+        assert!(page.contains(".SH NAME\nslc \\- Slices text\n"));
+        assert!(page.contains(".TP\n\\fIPATH\\fR\nPath to input file\n"));
+        assert!(page.contains(
+            ".TP\n\\fB\\-f\\fR, \\fB\\-\\-filters\\fR <\\fIFILTERS\\fR>\nFilters to be applied\n"
+        ));
+        assert!(!page.contains("secret"));
+        Ok(())
+    }
+
+    #[test]
+    fn generate_completions_presets_ok() -> io::Result<()> {
+        let mut app = App::new("slc");
+
+        let mut completions = Vec::new();
+        Generate::Completions(Shell::Bash).write(&mut app, &mut completions)?;
+        let completions = String::from_utf8(completions).unwrap();
+
+        assert!(completions.contains("_slc()"));
+        assert!(completions.contains("complete -F _slc_presets -o bashdefault -o default slc"));
+        Ok(())
+    }
+}
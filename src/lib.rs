@@ -1 +1,6 @@
 pub mod filter;
+pub mod generate;
+pub mod path;
+pub mod preset;
+pub mod record;
+pub mod size;
@@ -0,0 +1,219 @@
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::filter::{Filter, FilterSet, ParseError, Span};
+
+#[derive(Debug)]
+pub struct FieldPath {
+    name: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug)]
+struct Segment {
+    key: String,
+    positions: Option<FilterSet>,
+}
+
+impl FromStr for FieldPath {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut offset = 0;
+
+        for key in s.split('.') {
+            if key.is_empty() {
+                return Err(ParseError::InvalidFilter {
+                    reason: format!("path [{}] cannot contain an empty segment", s),
+                    span: Span::new(s, offset..offset),
+                });
+            }
+            offset += key.len() + 1;
+
+            let positions = Filter::from_str(key)
+                .ok()
+                .map(|filter| FilterSet::new(vec![filter]));
+
+            segments.push(Segment {
+                key: key.to_owned(),
+                positions,
+            });
+        }
+
+        Ok(FieldPath {
+            name: s.to_owned(),
+            segments,
+        })
+    }
+}
+
+impl FieldPath {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Selects the values found at this path, along with the concrete path to each of them.
+    /// Object keys are matched literally, while segments that are valid filters select elements
+    /// of arrays by their position, starting from one. A path that does not exist selects nothing.
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<(String, &'a Value)> {
+        let mut selected = vec![(String::new(), value)];
+
+        for segment in self.segments.iter() {
+            let mut next = Vec::new();
+
+            for (path, value) in selected {
+                let join = |key: &str| {
+                    if path.is_empty() {
+                        key.to_owned()
+                    } else {
+                        format!("{}.{}", path, key)
+                    }
+                };
+
+                match (value, &segment.positions) {
+                    (Value::Object(map), _) => {
+                        if let Some(value) = map.get(&segment.key) {
+                            next.push((join(&segment.key), value));
+                        }
+                    }
+                    (Value::Array(values), Some(positions)) => {
+                        let positions = positions.resolve(values.len() as u32);
+                        let mut matched: Vec<(u32, &Value)> = values
+                            .iter()
+                            .enumerate()
+                            .map(|(index, value)| (1 + index as u32, value))
+                            .filter(|&(index, _)| positions.apply(index))
+                            .collect();
+
+                        positions.reverse_ranges(&mut matched);
+                        next.extend(
+                            matched
+                                .into_iter()
+                                .map(|(index, value)| (join(&index.to_string()), value)),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+
+            selected = next;
+        }
+
+        selected
+    }
+
+    /// Removes the values found at this path, keeping the rest of the value and the order of
+    /// its keys as they were. A path that does not exist removes nothing.
+    pub fn remove(&self, value: &mut Value) {
+        remove(&self.segments, value);
+    }
+}
+
+fn remove(segments: &[Segment], value: &mut Value) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+
+    match (value, &segment.positions) {
+        (Value::Object(map), _) if rest.is_empty() => map.retain(|key, _| *key != segment.key),
+        (Value::Object(map), _) => {
+            if let Some(value) = map.get_mut(&segment.key) {
+                remove(rest, value);
+            }
+        }
+        (Value::Array(values), Some(positions)) => {
+            let positions = positions.resolve(values.len() as u32);
+            if rest.is_empty() {
+                let mut index = 0;
+                values.retain(|_| {
+                    index += 1;
+                    !positions.apply(index)
+                });
+            } else {
+                for (index, value) in values.iter_mut().enumerate() {
+                    if positions.apply(1 + index as u32) {
+                        remove(rest, value);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn path_parse_empty_segment_err() {
+        let path = FieldPath::from_str("http..status");
+
+        assert!(matches!(path, Err(ParseError::InvalidFilter { .. })));
+    }
+
+    #[test]
+    fn path_select_key_ok() -> Result<(), ParseError> {
+        let value = json!({"level": "info", "http": {"status": 200}});
+        let path = FieldPath::from_str("http.status")?;
+
+        assert_eq!(
+            path.select(&value),
+            vec![("http.status".to_owned(), &json!(200))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn path_select_position_ok() -> Result<(), ParseError> {
+        let value = json!({"tags": ["a", "b", "c"]});
+        let path = FieldPath::from_str("tags.2:")?;
+
+        assert_eq!(
+            path.select(&value),
+            vec![
+                ("tags.2".to_owned(), &json!("b")),
+                ("tags.3".to_owned(), &json!("c")),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn path_select_position_reversed_ok() -> Result<(), ParseError> {
+        let value = json!({"tags": ["a", "b", "c"]});
+        let path = FieldPath::from_str("tags.3:2")?;
+
+        assert_eq!(
+            path.select(&value),
+            vec![
+                ("tags.3".to_owned(), &json!("c")),
+                ("tags.2".to_owned(), &json!("b")),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn path_remove_nested_ok() -> Result<(), ParseError> {
+        let mut value = json!({"http": {"method": "GET", "status": 200}, "tags": ["a", "b"]});
+        FieldPath::from_str("http.method")?.remove(&mut value);
+        FieldPath::from_str("tags.1")?.remove(&mut value);
+
+        assert_eq!(value, json!({"http": {"status": 200}, "tags": ["b"]}));
+        Ok(())
+    }
+
+    #[test]
+    fn path_select_missing_empty() -> Result<(), ParseError> {
+        let value = json!({"level": "info"});
+        let path = FieldPath::from_str("level.name")?;
+
+        assert!(path.select(&value).is_empty());
+        Ok(())
+    }
+}
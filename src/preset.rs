@@ -0,0 +1,484 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{App, ArgSettings};
+use serde::Deserialize;
+use toml::Value;
+
+/// Presets are named sets of arguments defined in a config file, with a table for each binary:
+///
+/// ```toml
+/// [colslc]
+/// docker-images-short = "-f 1 2 7"
+///
+/// [colslc.json-status]
+/// input = "jsonl"
+/// output = "json"
+/// filters = ["level,http.status"]
+/// ```
+///
+/// A preset is either a string of arguments, or a table of options keyed by their long names.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(flatten)]
+    binaries: BTreeMap<String, BTreeMap<String, Preset>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Preset {
+    Args(String),
+    Options(BTreeMap<String, Value>),
+}
+
+impl FromStr for Config {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+impl Config {
+    /// Returns the path to the config file, which is given by the INSLICE_CONFIG environment
+    /// variable, or otherwise found at inslice/config.toml within the XDG config directory.
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os("INSLICE_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("inslice").join("config.toml"))
+    }
+
+    /// Loads the config file, returning an empty config if it does not exist.
+    pub fn load() -> Result<Self, PresetError> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+
+        let config = match fs::read_to_string(&path) {
+            Ok(config) => config,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(PresetError::ReadFailed { path, err }),
+        };
+
+        config
+            .parse()
+            .map_err(|err| PresetError::ParseFailed { path, err })
+    }
+
+    /// Returns the names of the presets defined for the given binary.
+    pub fn names(&self, binary: &str) -> Vec<&str> {
+        self.binaries
+            .get(binary)
+            .map(|presets| presets.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Expands each argument of the form @name into the arguments of the named preset, using
+    /// the definition of the binary's options to tell arguments apart from the values of
+    /// options. An @name given as the value of an option, or after --, is left as it is. The
+    /// arguments of presets are added after all other arguments, or just before --, so that
+    /// options taking multiple values, such as --filters, cannot consume any positional
+    /// arguments.
+    pub fn expand<I>(&self, app: &App, args: I) -> Result<Vec<OsString>, PresetError>
+    where
+        I: IntoIterator<Item = OsString>,
+    {
+        let binary = app.get_name();
+        let mut expanded = Vec::new();
+        let mut presets = Vec::new();
+        let mut value = false;
+        let mut escaped = None;
+
+        for (n, arg) in args.into_iter().enumerate() {
+            let text = arg.to_str().unwrap_or_default();
+            let preset = match text.strip_prefix('@') {
+                Some(name) if n > 0 && !value && escaped.is_none() => Some(name),
+                _ => None,
+            };
+
+            value = escaped.is_none() && takes_value(app, text);
+            if escaped.is_none() && text == "--" {
+                escaped = Some(expanded.len());
+            }
+
+            match preset {
+                Some(name) => presets.extend(self.preset(binary, name)?),
+                None => expanded.push(arg),
+            }
+        }
+
+        let at = escaped.unwrap_or(expanded.len());
+        expanded.splice(at..at, presets.into_iter().map(OsString::from));
+        Ok(expanded)
+    }
+
+    fn preset(&self, binary: &str, name: &str) -> Result<Vec<String>, PresetError> {
+        let preset = self
+            .binaries
+            .get(binary)
+            .and_then(|presets| presets.get(name))
+            .ok_or_else(|| PresetError::UnknownPreset {
+                name: name.to_owned(),
+                binary: binary.to_owned(),
+                available: self.names(binary).join(", "),
+            })?;
+
+        let invalid = |reason: String| PresetError::InvalidPreset {
+            name: name.to_owned(),
+            reason,
+        };
+
+        match preset {
+            Preset::Args(args) => split_args(args).map_err(invalid),
+            Preset::Options(options) => {
+                let mut args = Vec::new();
+                let mut filters = Vec::new();
+
+                for (key, value) in options {
+                    let flag = format!("--{}", key.replace('_', "-"));
+                    let values = match value {
+                        Value::Boolean(true) => vec![],
+                        Value::Boolean(false) => continue,
+                        Value::Array(values) => values
+                            .iter()
+                            .map(|value| option_value(key, value))
+                            .collect::<Result<_, _>>()
+                            .map_err(invalid)?,
+                        value => vec![option_value(key, value).map_err(invalid)?],
+                    };
+
+                    // Filters take multiple values, so they are given last and as a single
+                    // option. Other options are repeated for each of their values.
+                    if key == "filters" {
+                        filters.push(flag);
+                        filters.extend(values);
+                    } else if values.is_empty() {
+                        args.push(flag);
+                    } else {
+                        for value in values {
+                            args.push(flag.clone());
+                            args.push(value);
+                        }
+                    }
+                }
+
+                args.extend(filters);
+                Ok(args)
+            }
+        }
+    }
+}
+
+/// Returns whether the given argument is an option whose value is given as the next argument,
+/// such as `-d` or `--delimiter`, but not `--delimiter=,` or `-d,`.
+fn takes_value(app: &App, arg: &str) -> bool {
+    let takes_value = |option: &clap::Arg| option.is_set(ArgSettings::TakesValue);
+
+    if let Some(long) = arg.strip_prefix("--") {
+        return !long.contains('=')
+            && app
+                .get_arguments()
+                .any(|option| option.get_long() == Some(long) && takes_value(option));
+    }
+
+    // Short flags can be combined, such as -zd, where only the last can take the next argument
+    // as its value, while any other short option takes the rest of the argument as its value.
+    let shorts = match arg.strip_prefix('-') {
+        Some(shorts) if !shorts.is_empty() => shorts,
+        _ => return false,
+    };
+    for (i, short) in shorts.char_indices() {
+        if app
+            .get_arguments()
+            .any(|option| option.get_short() == Some(short) && takes_value(option))
+        {
+            return i + short.len_utf8() == shorts.len();
+        }
+    }
+
+    false
+}
+
+/// Expands presets in the given arguments using the config file, which is only loaded if any
+/// presets are used.
+pub fn expand_args<I>(app: &App, args: I) -> Result<Vec<OsString>, PresetError>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let args: Vec<OsString> = args.into_iter().collect();
+
+    let used = args
+        .iter()
+        .skip(1)
+        .any(|arg| arg.to_str().is_some_and(|arg| arg.starts_with('@')));
+    if !used {
+        return Ok(args);
+    }
+
+    Config::load()?.expand(app, args)
+}
+
+fn option_value(key: &str, value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Integer(n) => Ok(n.to_string()),
+        Value::Float(n) => Ok(n.to_string()),
+        _ => Err(format!("unsupported value for option [{}]: {}", key, value)),
+    }
+}
+
+/// Splits a string of arguments on whitespace, treating text within single or double quotes as
+/// part of a single argument.
+fn split_args(s: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut quote: Option<char> = None;
+
+    for c in s.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => arg.get_or_insert_with(String::new).push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                arg.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(arg.take()),
+            (None, c) => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if let Some(q) = quote {
+        return Err(format!("unterminated {} quote in [{}]", q, s));
+    }
+
+    args.extend(arg);
+    Ok(args)
+}
+
+#[derive(Debug)]
+pub enum PresetError {
+    ReadFailed {
+        path: PathBuf,
+        err: io::Error,
+    },
+    ParseFailed {
+        path: PathBuf,
+        err: toml::de::Error,
+    },
+    UnknownPreset {
+        name: String,
+        binary: String,
+        available: String,
+    },
+    InvalidPreset {
+        name: String,
+        reason: String,
+    },
+}
+
+impl Error for PresetError {}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::ReadFailed { ref path, ref err } => {
+                write!(f, "failed to read config file {}: {}", path.display(), err)
+            }
+            Self::ParseFailed { ref path, ref err } => {
+                write!(f, "failed to parse config file {}: {}", path.display(), err)
+            }
+            Self::UnknownPreset {
+                ref name,
+                ref binary,
+                ref available,
+            } => {
+                write!(f, "unknown preset @{} for {}", name, binary)?;
+                match available.as_str() {
+                    "" => match Config::path() {
+                        Some(path) if path.exists() => {
+                            write!(f, ", no presets are defined in {}", path.display())
+                        }
+                        Some(path) => write!(f, ", no config file found at {}", path.display()),
+                        None => write!(f, ", no config file could be found"),
+                    },
+                    available => write!(f, ", available presets are: {}", available),
+                }
+            }
+            Self::InvalidPreset {
+                ref name,
+                ref reason,
+            } => write!(f, "invalid preset @{}: {}", name, reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[colslc]
+docker-images-short = "-f 1 2 7"
+quoted = "-d ', ' -f 2"
+
+[colslc.json-status]
+input = "jsonl"
+output = "json"
+header = true
+infer_types = false
+filters = ["level,http.status"]
+
+[rowslc]
+skip-header = "-f 2:"
+"#;
+
+    fn app(name: &'static str) -> App<'static> {
+        App::new(name)
+            .arg(clap::Arg::new("path"))
+            .arg(
+                clap::Arg::new("filters")
+                    .short('f')
+                    .long("filters")
+                    .takes_value(true)
+                    .multiple_values(true),
+            )
+            .arg(
+                clap::Arg::new("delimiter")
+                    .short('d')
+                    .long("delimiter")
+                    .takes_value(true),
+            )
+            .arg(clap::Arg::new("header").long("header"))
+            .arg(clap::Arg::new("null-data").short('z'))
+    }
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn preset_expand_args_ok() -> Result<(), Box<dyn Error>> {
+        let config = Config::from_str(CONFIG)?;
+        let expanded = config.expand(
+            &app("colslc"),
+            args(&["colslc", "@docker-images-short", "-"]),
+        )?;
+
+        assert_eq!(expanded, args(&["colslc", "-", "-f", "1", "2", "7"]));
+        Ok(())
+    }
+
+    #[test]
+    fn preset_expand_quoted_ok() -> Result<(), Box<dyn Error>> {
+        let config = Config::from_str(CONFIG)?;
+        let expanded = config.expand(&app("colslc"), args(&["colslc", "@quoted"]))?;
+
+        assert_eq!(expanded, args(&["colslc", "-d", ", ", "-f", "2"]));
+        Ok(())
+    }
+
+    #[test]
+    fn preset_expand_options_ok() -> Result<(), Box<dyn Error>> {
+        let config = Config::from_str(CONFIG)?;
+        let expanded = config.expand(
+            &app("colslc"),
+            args(&["colslc", "@json-status", "logs.jsonl"]),
+        )?;
+
+        assert_eq!(
+            expanded,
+            args(&[
+                "colslc",
+                "logs.jsonl",
+                "--header",
+                "--input",
+                "jsonl",
+                "--output",
+                "json",
+                "--filters",
+                "level,http.status",
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn preset_expand_option_value_ok() -> Result<(), Box<dyn Error>> {
+        let config = Config::from_str(CONFIG)?;
+
+        let expanded = config.expand(&app("colslc"), args(&["colslc", "-d", "@", "-f", "1"]))?;
+        assert_eq!(expanded, args(&["colslc", "-d", "@", "-f", "1"]));
+
+        let expanded = config.expand(&app("colslc"), args(&["colslc", "-zd", "@", "@quoted"]))?;
+        assert_eq!(
+            expanded,
+            args(&["colslc", "-zd", "@", "-d", ", ", "-f", "2"])
+        );
+
+        let expanded = config.expand(&app("colslc"), args(&["colslc", "--header", "@quoted"]))?;
+        assert_eq!(
+            expanded,
+            args(&["colslc", "--header", "-d", ", ", "-f", "2"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn preset_expand_escaped_ok() -> Result<(), Box<dyn Error>> {
+        let config = Config::from_str(CONFIG)?;
+        let expanded =
+            config.expand(&app("colslc"), args(&["colslc", "@quoted", "--", "@file"]))?;
+
+        assert_eq!(
+            expanded,
+            args(&["colslc", "-d", ", ", "-f", "2", "--", "@file"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn preset_expand_unknown_err() -> Result<(), Box<dyn Error>> {
+        let config = Config::from_str(CONFIG)?;
+        let expanded = config.expand(&app("rowslc"), args(&["rowslc", "@docker-images-short"]));
+
+        match expanded {
+            Err(err @ PresetError::UnknownPreset { .. }) => assert_eq!(
+                err.to_string(),
+                "unknown preset @docker-images-short for rowslc, available presets are: skip-header"
+            ),
+            _ => panic!("expected unknown preset error"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn preset_names_ok() -> Result<(), Box<dyn Error>> {
+        let config = Config::from_str(CONFIG)?;
+
+        assert_eq!(
+            config.names("colslc"),
+            vec!["docker-images-short", "json-status", "quoted"]
+        );
+        assert!(config.names("other").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn split_args_unterminated_err() {
+        assert!(split_args("-d '").is_err());
+    }
+}
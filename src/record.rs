@@ -0,0 +1,322 @@
+use std::io::{self, BufRead, Write};
+
+use clap::ArgEnum;
+
+/// An extension of BufRead for reading records that are terminated by an arbitrary separator,
+/// rather than only by a newline.
+pub trait ReadRecord: BufRead {
+    /// Reads bytes until the given separator is found or EOF is reached, appending them to buf
+    /// along with the separator itself, if found. Returns the number of bytes read, which is
+    /// zero once EOF is reached. Like read_line, the data read must be valid UTF-8.
+    fn read_record(&mut self, buf: &mut String, separator: &str) -> io::Result<usize> {
+        if separator == "\n" {
+            return self.read_line(buf);
+        }
+
+        let separator = separator.as_bytes();
+        let last = match separator.last() {
+            Some(&last) => last,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "empty separator",
+                ))
+            }
+        };
+
+        let mut record = Vec::new();
+        loop {
+            let n = self.read_until(last, &mut record)?;
+            if n == 0 || record.ends_with(separator) {
+                break;
+            }
+        }
+
+        let record = std::str::from_utf8(&record)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        buf.push_str(record);
+
+        Ok(record.len())
+    }
+}
+
+impl<R: BufRead + ?Sized> ReadRecord for R {}
+
+/// Splits a record into its content and its terminator, which is empty if the record is not
+/// terminated. When records are separated by newlines, a terminating carriage return is treated
+/// as part of the terminator, so that lines ending in CRLF are handled the same as those ending
+/// in LF.
+pub fn split_terminator<'a>(record: &'a str, separator: &str) -> (&'a str, &'a str) {
+    let content = match record.strip_suffix(separator) {
+        Some(content) if separator == "\n" => content.strip_suffix('\r').unwrap_or(content),
+        Some(content) => content,
+        None => record,
+    };
+
+    record.split_at(content.len())
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Preserve,
+}
+
+impl LineEnding {
+    /// Returns the line ending to write in place of the given terminator. Terminators other than
+    /// LF and CRLF, including the empty terminator of the last line, are left unchanged.
+    pub fn apply(self, terminator: &str) -> &str {
+        match (self, terminator) {
+            (Self::Lf, "\n") | (Self::Lf, "\r\n") => "\n",
+            (Self::Crlf, "\n") | (Self::Crlf, "\r\n") => "\r\n",
+            (_, terminator) => terminator,
+        }
+    }
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum FinalNewline {
+    Always,
+    Preserve,
+}
+
+/// A RecordWriter terminates the records written to it consistently. Each LF written to it is
+/// translated into CRLF when enabled, so that output written line by line can match the line
+/// endings of the input text. The terminator at the end of the output is held back until more
+/// output is written or the writer is finished, so that it can be left out when the input text
+/// was not terminated either.
+pub struct RecordWriter<W: Write> {
+    inner: W,
+    terminator: String,
+    crlf: bool,
+    pending: Option<Vec<u8>>,
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(inner: W, terminator: &str, line_ending: LineEnding) -> Self {
+        Self {
+            inner,
+            terminator: terminator.to_owned(),
+            crlf: line_ending == LineEnding::Crlf,
+            pending: None,
+        }
+    }
+
+    /// Sets whether subsequent lines should be terminated by CRLF, based on the terminator of
+    /// the line of input text being written when preserving line endings.
+    pub fn preserve(&mut self, line_ending: LineEnding, terminator: &str) {
+        if line_ending == LineEnding::Preserve && !terminator.is_empty() {
+            self.crlf = terminator == "\r\n";
+        }
+    }
+
+    /// Writes the terminator held back at the end of the output, if any, unless told to leave
+    /// it out, and flushes the underlying writer.
+    pub fn finish(&mut self, terminate: bool) -> io::Result<()> {
+        if let Some(pending) = self.pending.take() {
+            if terminate {
+                self.inner.write_all(&pending)?;
+            }
+        }
+
+        self.inner.flush()
+    }
+
+    fn write_translated(&mut self, buf: &[u8]) -> io::Result<()> {
+        if !self.crlf {
+            return self.inner.write_all(buf);
+        }
+
+        for (n, line) in buf.split(|&b| b == b'\n').enumerate() {
+            if n > 0 {
+                self.inner.write_all(b"\r\n")?;
+            }
+            self.inner.write_all(line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for RecordWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(pending) = self.pending.take() {
+            self.inner.write_all(&pending)?;
+        }
+
+        match buf.strip_suffix(self.terminator.as_bytes()) {
+            Some(content) => {
+                self.write_translated(content)?;
+
+                let mut pending = Vec::new();
+                if self.crlf && self.terminator == "\n" {
+                    pending.extend_from_slice(b"\r\n");
+                } else {
+                    pending.extend_from_slice(self.terminator.as_bytes());
+                }
+                self.pending = Some(pending);
+            }
+            None => self.write_translated(buf)?,
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Parses a record separator given on the command-line, decoding the `\0`, `\n`, `\r`, `\t` and
+/// `\\` escape sequences, along with `\xHH` for any other ASCII character.
+pub fn parse_separator(s: &str) -> Result<String, String> {
+    let mut separator = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            separator.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('0') => separator.push('\0'),
+            Some('n') => separator.push('\n'),
+            Some('r') => separator.push('\r'),
+            Some('t') => separator.push('\t'),
+            Some('\\') => separator.push('\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(n) if hex.len() == 2 && n.is_ascii() => separator.push(n as char),
+                    _ => return Err(format!("invalid escape sequence \\x{} in [{}]", hex, s)),
+                }
+            }
+            Some(c) => return Err(format!("invalid escape sequence \\{} in [{}]", c, s)),
+            None => return Err(format!("incomplete escape sequence in [{}]", s)),
+        }
+    }
+
+    if separator.is_empty() {
+        return Err("record separator cannot be empty".to_owned());
+    }
+
+    Ok(separator)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read_all(input: &str, separator: &str) -> io::Result<Vec<String>> {
+        let mut reader = input.as_bytes();
+        let mut records = Vec::new();
+
+        loop {
+            let mut buf = String::new();
+            if reader.read_record(&mut buf, separator)? == 0 {
+                break;
+            }
+            records.push(buf);
+        }
+
+        Ok(records)
+    }
+
+    #[test]
+    fn read_record_null_ok() -> io::Result<()> {
+        let records = read_all("a b\0c\nd\0e", "\0")?;
+
+        assert_eq!(records, vec!["a b\0", "c\nd\0", "e"]);
+        Ok(())
+    }
+
+    #[test]
+    fn read_record_multibyte_ok() -> io::Result<()> {
+        let records = read_all("a;b;;c;;;d", ";;")?;
+
+        assert_eq!(records, vec!["a;b;;", "c;;", ";d"]);
+        Ok(())
+    }
+
+    #[test]
+    fn read_record_newline_ok() -> io::Result<()> {
+        let records = read_all("a\nb\n", "\n")?;
+
+        assert_eq!(records, vec!["a\n", "b\n"]);
+        Ok(())
+    }
+
+    #[test]
+    fn split_terminator_ok() {
+        assert_eq!(split_terminator("a b\r\n", "\n"), ("a b", "\r\n"));
+        assert_eq!(split_terminator("a b\n", "\n"), ("a b", "\n"));
+        assert_eq!(split_terminator("a b", "\n"), ("a b", ""));
+        assert_eq!(split_terminator("a\r\0", "\0"), ("a\r", "\0"));
+    }
+
+    #[test]
+    fn line_ending_apply_ok() {
+        assert_eq!(LineEnding::Lf.apply("\r\n"), "\n");
+        assert_eq!(LineEnding::Crlf.apply("\n"), "\r\n");
+        assert_eq!(LineEnding::Preserve.apply("\r\n"), "\r\n");
+        assert_eq!(LineEnding::Crlf.apply(""), "");
+        assert_eq!(LineEnding::Crlf.apply("\0"), "\0");
+    }
+
+    #[test]
+    fn record_writer_crlf_ok() -> io::Result<()> {
+        let mut writer = RecordWriter::new(Vec::new(), "\n", LineEnding::Crlf);
+        write!(writer, "a\nb")?;
+        writeln!(writer)?;
+        writer.finish(true)?;
+
+        assert_eq!(writer.inner, b"a\r\nb\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn record_writer_preserve_ok() -> io::Result<()> {
+        let mut writer = RecordWriter::new(Vec::new(), "\n", LineEnding::Preserve);
+        writer.preserve(LineEnding::Preserve, "\r\n");
+        writeln!(writer, "a")?;
+        writer.preserve(LineEnding::Preserve, "\n");
+        writeln!(writer, "b")?;
+        writer.finish(true)?;
+
+        assert_eq!(writer.inner, b"a\r\nb\n");
+        Ok(())
+    }
+
+    #[test]
+    fn record_writer_unterminated_ok() -> io::Result<()> {
+        let mut writer = RecordWriter::new(Vec::new(), "\0", LineEnding::Preserve);
+        write!(writer, "a\0")?;
+        write!(writer, "b\0")?;
+        writer.finish(false)?;
+
+        assert_eq!(writer.inner, b"a\0b");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_separator_escapes_ok() -> Result<(), String> {
+        assert_eq!(parse_separator("\\0")?, "\0");
+        assert_eq!(parse_separator("\\r\\n")?, "\r\n");
+        assert_eq!(parse_separator("\\x1e")?, "\x1e");
+        assert_eq!(parse_separator(";")?, ";");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_separator_invalid_err() {
+        assert!(parse_separator("\\q").is_err());
+        assert!(parse_separator("\\x9").is_err());
+        assert!(parse_separator("").is_err());
+    }
+}
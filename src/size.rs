@@ -0,0 +1,186 @@
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// A size in bytes, which can be parsed from a number with an optional K, M or G suffix denoting
+/// kibibytes, mebibytes or gibibytes respectively, such as `512K` or `64M`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ByteSize(pub u64);
+
+impl FromStr for ByteSize {
+    type Err = ParseSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (n, suffix) = s.split_at(split);
+
+        let multiplier: u64 = match suffix.to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "K" | "KB" | "KIB" => 1 << 10,
+            "M" | "MB" | "MIB" => 1 << 20,
+            "G" | "GB" | "GIB" => 1 << 30,
+            _ => {
+                return Err(ParseSizeError::InvalidSize {
+                    reason: format!("unknown suffix [{}] in size [{}]", suffix, s),
+                })
+            }
+        };
+
+        n.parse::<u64>()?
+            .checked_mul(multiplier)
+            .map(ByteSize)
+            .ok_or_else(|| ParseSizeError::InvalidSize {
+                reason: format!("size [{}] is too large", s),
+            })
+    }
+}
+
+/// A range of bytes, given as `start:end`, where each offset is a size such as `1G`, or a sum of
+/// sizes such as `1G+4M`. Either offset can be left out, for the range to start from the first
+/// byte or end at the last byte. An end beginning with `+` is relative to the start, such that
+/// `1G:+4M` is the same as `1G:1G+4M`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: Option<u64>,
+}
+
+impl FromStr for ByteRange {
+    type Err = ParseSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once(':')
+            .ok_or_else(|| ParseSizeError::InvalidSize {
+                reason: format!("byte range [{}] must be of the form start:end", s),
+            })?;
+
+        let start = match start {
+            "" => 0,
+            start => parse_offset(start)?,
+        };
+
+        let end = match end {
+            "" => None,
+            end => match end.strip_prefix('+') {
+                Some(length) => {
+                    Some(start.checked_add(parse_offset(length)?).ok_or_else(|| {
+                        ParseSizeError::InvalidSize {
+                            reason: format!("byte range [{}] is too large", s),
+                        }
+                    })?)
+                }
+                None => Some(parse_offset(end)?),
+            },
+        };
+
+        match end {
+            Some(end) if end < start => Err(ParseSizeError::InvalidSize {
+                reason: format!("byte range [{}] cannot end before it starts", s),
+            }),
+            end => Ok(ByteRange { start, end }),
+        }
+    }
+}
+
+/// Parses an offset given as a sum of sizes, such as `1G+4M`.
+fn parse_offset(s: &str) -> Result<u64, ParseSizeError> {
+    s.split('+').try_fold(0u64, |offset, size| {
+        let ByteSize(size) = size.parse()?;
+        offset
+            .checked_add(size)
+            .ok_or_else(|| ParseSizeError::InvalidSize {
+                reason: format!("offset [{}] is too large", s),
+            })
+    })
+}
+
+#[derive(Debug)]
+pub enum ParseSizeError {
+    ParseIntFailed(ParseIntError),
+    InvalidSize { reason: String },
+}
+
+impl From<ParseIntError> for ParseSizeError {
+    fn from(error: ParseIntError) -> Self {
+        Self::ParseIntFailed(error)
+    }
+}
+
+impl Error for ParseSizeError {}
+
+impl fmt::Display for ParseSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::ParseIntFailed(ref err) => write!(f, "failed to parse size: {}", err),
+            Self::InvalidSize { ref reason } => write!(f, "invalid size: {}", reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn size_parse_bytes_ok() -> Result<(), ParseSizeError> {
+        assert_eq!(ByteSize::from_str("512")?, ByteSize(512));
+        Ok(())
+    }
+
+    #[test]
+    fn size_parse_suffix_ok() -> Result<(), ParseSizeError> {
+        assert_eq!(ByteSize::from_str("4K")?, ByteSize(4096));
+        assert_eq!(ByteSize::from_str("64m")?, ByteSize(64 << 20));
+        assert_eq!(ByteSize::from_str("2GiB")?, ByteSize(2 << 30));
+        Ok(())
+    }
+
+    #[test]
+    fn size_parse_suffix_err() {
+        let size = ByteSize::from_str("4X");
+
+        assert!(matches!(size, Err(ParseSizeError::InvalidSize { .. })));
+    }
+
+    #[test]
+    fn size_parse_non_numeric_err() {
+        let size = ByteSize::from_str("M");
+
+        assert!(matches!(size, Err(ParseSizeError::ParseIntFailed(_))));
+    }
+
+    #[test]
+    fn byte_range_parse_ok() -> Result<(), ParseSizeError> {
+        assert_eq!(
+            ByteRange::from_str("1G:1G+4M")?,
+            ByteRange {
+                start: 1 << 30,
+                end: Some((1 << 30) + (4 << 20)),
+            }
+        );
+        assert_eq!(
+            ByteRange::from_str("1K:+1K")?,
+            ByteRange {
+                start: 1024,
+                end: Some(2048),
+            }
+        );
+        assert_eq!(
+            ByteRange::from_str("512:")?,
+            ByteRange {
+                start: 512,
+                end: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn byte_range_parse_err() {
+        assert!(ByteRange::from_str("1G").is_err());
+        assert!(ByteRange::from_str("2K:1K").is_err());
+        assert!(ByteRange::from_str("1G:+4X").is_err());
+    }
+}